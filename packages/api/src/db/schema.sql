@@ -49,7 +49,8 @@ CREATE TABLE IF NOT EXISTS reviews (
     comment TEXT,
     grade_received TEXT,  -- A, A-, B+, etc.
     
-    -- Anti-spam: ZK nullifier (hash of session + faculty + semester)
+    -- Anti-spam: review_nullifier output of bracu_review_verifier.circom (zk-wasm derive_review_nullifier),
+    -- keyed by a secret only the student holds
     nullifier TEXT NOT NULL UNIQUE,
     
     -- Metadata
@@ -0,0 +1,52 @@
+pragma circom 2.1.5;
+
+include "@zk-email/circuits/email-verifier.circom";
+include "@zk-email/circuits/utils/regex.circom";
+include "@zk-email/zk-regex-circom/circuits/common/from_addr_regex.circom";
+include "./bracu_regex.circom";
+include "./nullifier.circom";
+
+// BracuVerifier for reviews: instead of a per-email nullifier it outputs one
+// nullifier per student, faculty and semester, derived from the signed From
+// address and the student's private secret. The email must be one the
+// student sent, so that From is theirs.
+template BracuReviewVerifier(max_header_bytes, max_body_bytes, n, k, max_address_bytes) {
+    signal input in_padded[max_header_bytes]; // Preprocessed email header
+    signal input pubkey[k];                   // RSA Public Key (Google)
+    signal input signature[k];                // RSA Signature
+    signal input in_len_padded_bytes;         // Length of padded header
+    signal input from_address_index;          // Start of the From address in in_padded
+    signal input secret;                      // Student's nullifier secret (private)
+    signal input faculty;                     // Packed faculty initials (public)
+    signal input semester;                    // Packed semester (public)
+
+    // Outputs
+    signal output pubkey_hash;
+    signal output review_nullifier;
+
+    // 1. Core Email Verification (DKIM)
+    component emailVerifier = EmailVerifier(max_header_bytes, max_body_bytes, n, k, 0);
+    emailVerifier.in_padded <== in_padded;
+    emailVerifier.pubkey <== pubkey;
+    emailVerifier.signature <== signature;
+    emailVerifier.in_len_padded_bytes <== in_len_padded_bytes;
+    pubkey_hash <== emailVerifier.pubkey_hash;
+
+    // 2. Domain Verification (@g.bracu.ac.bd)
+    component regex = BracuRegex(max_header_bytes);
+    regex.msg <== in_padded;
+    regex.out === 1;
+
+    // 3. The signed From address, revealed by zk-email's from_addr regex
+    signal (from_found, from_reveal[max_header_bytes]) <== FromAddrRegex(max_header_bytes)(in_padded);
+    from_found === 1;
+    signal from_address[max_address_bytes] <== SelectRegexReveal(max_header_bytes, max_address_bytes)(from_reveal, from_address_index);
+
+    // 4. The student's email nullifier, then the review nullifier bound to
+    //    the public faculty and semester; only the latter is revealed
+    signal email_nullifier <== AddressNullifier(max_address_bytes)(from_address, secret);
+    review_nullifier <== ReviewNullifier()(email_nullifier, faculty, semester);
+}
+
+// RSA-2048 (121 * 17), addresses up to 64 bytes (zk-wasm MAX_ADDRESS_BYTES)
+component main { public [faculty, semester] } = BracuReviewVerifier(1024, 0, 121, 17, 64);
@@ -2,6 +2,7 @@ pragma circom 2.1.5;
 
 include "@zk-email/circuits/email-verifier.circom";
 include "./bracu_regex.circom";
+include "./nullifier.circom";
 
 template BracuVerifier(max_header_bytes, max_body_bytes, n, k, pack_size) {
     signal input in_padded[max_header_bytes]; // Preprocessed email header
@@ -30,10 +31,10 @@ template BracuVerifier(max_header_bytes, max_body_bytes, n, k, pack_size) {
     regex.out === 1;
 
     // 3. Nullifier Generation
-    // We use the email signature as the base for the nullifier to ensure uniqueness
-    // but preventing linkability (since signature is unique per email)
-    // Note: In a real voting system, we might hash (signature + external_nullifier)
-    nullifier <== emailVerifier.pubkey_hash; // Placeholder: Real logic needs Poseidon(signature)
+    // Poseidon of the signature limbs: unique per email, so the same email
+    // cannot be used twice. A student with several emails gets several
+    // nullifiers; reviews use bracu_review_verifier's per-student nullifier.
+    nullifier <== EmailNullifier(k)(signature);
 }
 
 // Main component with standard sizes
@@ -0,0 +1,99 @@
+pragma circom 2.1.5;
+
+include "circomlib/circuits/poseidon.circom";
+include "@zk-email/circuits/utils/bytes.circom";
+
+// Poseidon over any number of elements: chunks of 16 (the last one may be
+// shorter) are hashed separately and chained as acc = Poseidon(acc, chunk).
+// Mirrored by `poseidon_modular` in packages/zk-wasm/src/poseidon.rs.
+template PoseidonModular(n) {
+    signal input in[n];
+    signal output out;
+
+    var chunks = (n + 15) \ 16;
+    component chunkHash[chunks];
+    component chain[chunks];
+    signal acc[chunks];
+
+    for (var c = 0; c < chunks; c++) {
+        var size = n - c * 16 < 16 ? n - c * 16 : 16;
+        chunkHash[c] = Poseidon(size);
+        for (var i = 0; i < size; i++) {
+            chunkHash[c].inputs[i] <== in[c * 16 + i];
+        }
+        if (c == 0) {
+            acc[c] <== chunkHash[c].out;
+        } else {
+            chain[c] = Poseidon(2);
+            chain[c].inputs[0] <== acc[c - 1];
+            chain[c].inputs[1] <== chunkHash[c].out;
+            acc[c] <== chain[c].out;
+        }
+    }
+
+    out <== acc[chunks - 1];
+}
+
+// Nullifier of an email: the hash of its DKIM RSA signature limbs
+template EmailNullifier(k) {
+    signal input signature[k];
+    signal output out;
+
+    out <== PoseidonModular(k)(signature);
+}
+
+// Email nullifier scoped to a faculty and semester
+template ExternalNullifier(k) {
+    signal input signature[k];
+    signal input faculty_id;
+    signal input semester;
+    signal output out;
+
+    signal email_nullifier <== EmailNullifier(k)(signature);
+    out <== Poseidon(3)([email_nullifier, faculty_id, semester]);
+}
+
+// Hash of an email address zero-filled to max_bytes: PackBytes (31 bytes per
+// element) then PoseidonModular. Mirrored by `address_hash` in zk-wasm.
+template AddressHash(max_bytes) {
+    signal input address[max_bytes];
+    signal output out;
+
+    var chunks = (max_bytes + 30) \ 31;
+    signal packed[chunks] <== PackBytes(max_bytes)(address);
+    out <== PoseidonModular(chunks)(packed);
+}
+
+// A student's email nullifier: Poseidon(domain, AddressHash(from address),
+// secret). The secret never leaves the prover, so the address alone does not
+// give the value. Mirrored by `address_nullifier` in zk-wasm.
+template AddressNullifier(max_address_bytes) {
+    signal input address[max_address_bytes];
+    signal input secret;
+    signal output out;
+
+    // "bracu-address-nullifier-v1" behind a 0x01 byte, big-endian
+    var ADDRESS_DOMAIN = 569574045224753442742071884167210502946750949534630645544613425;
+
+    // A zero secret would make the value computable from the address
+    signal secret_inverse <-- secret != 0 ? 1 / secret : 0;
+    secret * secret_inverse === 1;
+
+    signal address_hash <== AddressHash(max_address_bytes)(address);
+    out <== Poseidon(3)([ADDRESS_DOMAIN, address_hash, secret]);
+}
+
+// Nullifier of a review, one per student, faculty and semester:
+// Poseidon(domain, email nullifier, faculty, semester).
+// Mirrored by `review_nullifier` in zk-wasm.
+template ReviewNullifier() {
+    signal input email_nullifier;
+    signal input faculty;
+    signal input semester;
+    signal output out;
+
+    // "bracu-review-nullifier-v1" behind a 0x01 byte, big-endian
+    var REVIEW_DOMAIN = 2224898614159193514917112078108738299973290827250918507771441;
+
+    out <== Poseidon(4)([REVIEW_DOMAIN, email_nullifier, faculty, semester]);
+}
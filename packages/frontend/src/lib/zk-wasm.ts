@@ -21,6 +21,21 @@ async function loadWasmModule() {
   }
 }
 
+/**
+ * Error thrown by zk-wasm functions. `code` is stable (e.g.
+ * `FROM_HEADER_TOO_EARLY`, `NO_DKIM_SIGNATURE`, `KEY_NOT_LOADED`) and
+ * `details` holds the values behind the message.
+ */
+export interface ZkWasmError extends Error {
+  name: 'ZkWasmError';
+  code: string;
+  details: Record<string, unknown>;
+}
+
+export function isZkWasmError(error: unknown): error is ZkWasmError {
+  return error instanceof Error && error.name === 'ZkWasmError' && 'code' in error;
+}
+
 export async function initZK() {
   const module = await loadWasmModule();
   if (!module) {
@@ -125,6 +140,48 @@ export async function computePartialHash(
   }
 }
 
+export interface PrecomputedHeader extends PartialHashResult {
+  /** From header index, relative to `remaining` */
+  fromHeaderIndex: number;
+  fromHeaderLength: number;
+  /** From address index, relative to `remaining` */
+  fromAddressIndex: number;
+  fromAddressLength: number;
+  fromEmail: string;
+}
+
+/**
+ * Precompute the signed header and locate the From header in one step.
+ *
+ * Unlike `computePartialHash` + `parseDKIMFromEmail`, the indices refer to
+ * the same buffer the circuit receives (`remaining`), and the From header is
+ * guaranteed to lie inside it.
+ */
+export async function precomputeEmailHeader(
+  emailBytes: Uint8Array,
+  maxRemainingLen: number = 2048
+): Promise<PrecomputedHeader> {
+  const module = await loadWasmModule();
+
+  if (!module || typeof module.precompute_email_header !== 'function') {
+    throw new Error('precompute_email_header not found in WASM module. Please rebuild the WASM.');
+  }
+
+  const result = module.precompute_email_header(emailBytes, maxRemainingLen);
+  const partial = result.partial_hash;
+  return {
+    state: new Uint32Array(partial.state),
+    remaining: new Uint8Array(partial.remaining),
+    totalLength: Number(partial.total_length),
+    prehashedLength: Number(partial.prehashed_length),
+    fromHeaderIndex: Number(result.from_header_index),
+    fromHeaderLength: Number(result.from_header_length),
+    fromAddressIndex: Number(result.from_address_index),
+    fromAddressLength: Number(result.from_address_length),
+    fromEmail: result.from_email,
+  };
+}
+
 /**
  * Fallback: compute partial hash in pure JavaScript
  * Used when WASM module is not available
@@ -202,8 +259,10 @@ export interface DKIMResult {
   pubkeyModulus: string[];
   pubkeyRedc: string[];
   signature: string[];
+  /** From header index, relative to the canonicalized signed header */
   fromHeaderIndex: number;
   fromHeaderLength: number;
+  /** From address index, relative to the canonicalized signed header */
   fromAddressIndex: number;
   fromAddressLength: number;
   fromEmail: string;
@@ -267,6 +326,69 @@ export async function parseDKIMFromEmail(emailBytes: Uint8Array): Promise<DKIMRe
   }
 }
 
+/**
+ * Generate the full input object of the circom `bracu_verifier` circuit.
+ *
+ * Header canonicalization, SHA-256 padding and zero-filling all happen in
+ * WASM. The DKIM key is fetched from DNS unless `pubkey` is given. With
+ * `targetDomain`, only a signature with that `d=` is accepted; otherwise the
+ * one aligned with the From domain is used.
+ */
+export async function generateCircomInputs(
+  emailBytes: Uint8Array,
+  pubkey?: string,
+  maxHeaderBytes: number = 1024,
+  targetDomain?: string
+): Promise<Record<string, string | string[]>> {
+  const module = await loadWasmModule();
+
+  if (!module) {
+    throw new Error('zk-wasm module not available');
+  }
+
+  if (typeof module.generate_circom_inputs !== 'function') {
+    throw new Error('generate_circom_inputs not found in WASM module. Please rebuild the WASM.');
+  }
+
+  if (!pubkey) {
+    const dkim = module.parse_dkim_from_email(emailBytes, targetDomain);
+    pubkey = await fetchDkimPublicKey(dkim.selector, dkim.domain);
+  }
+
+  return JSON.parse(module.generate_circom_inputs(emailBytes, pubkey, maxHeaderBytes, targetDomain));
+}
+
+/**
+ * Generate the input map of the Noir `bracu_verifier` circuit (partial
+ * header hash, remaining header, pubkey, signature and From sequences).
+ *
+ * Sequence indices are relative to `remaining_header` and bounds-checked in
+ * WASM. The DKIM key is fetched from DNS unless `pubkey` is given.
+ */
+export async function generateNoirInputs(
+  emailBytes: Uint8Array,
+  pubkey?: string,
+  maxRemainingLen: number = 2048
+): Promise<Record<string, any>> {
+  const module = await loadWasmModule();
+
+  if (!module) {
+    throw new Error('zk-wasm module not available');
+  }
+
+  if (typeof module.generate_noir_inputs !== 'function') {
+    throw new Error('generate_noir_inputs not found in WASM module. Please rebuild the WASM.');
+  }
+
+  if (!pubkey) {
+    const dkim = module.parse_dkim_from_email(emailBytes);
+    pubkey = await fetchDkimPublicKey(dkim.selector, dkim.domain);
+  }
+
+  const inputs = module.generate_noir_inputs(emailBytes, pubkey, maxRemainingLen);
+  return JSON.parse(inputs.to_json());
+}
+
 /**
  * Fetch DKIM Public Key from DNS using Google DoH
  */
@@ -0,0 +1,565 @@
+//! `zkemail-inputs`: inspect `.eml` files and generate circuit inputs natively.
+//!
+//! Replaces the ad-hoc Node debug scripts: it shows exactly what the circuits
+//! will see (signed header, split point, From indices) using the same code
+//! that the browser runs through WASM.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::process::ExitCode;
+
+use zk_wasm::canonicalization::parse_message;
+use zk_wasm::circuit_inputs::{circom_inputs, noir_inputs, precompute_header};
+use zk_wasm::dkim::{self, DkimSignature, SignaturePolicy};
+use zk_wasm::dkim_key::parse_public_key;
+use zk_wasm::error::ZkWasmError;
+use zk_wasm::key_registry::KeyRegistry;
+use zk_wasm::rsa::RsaPublicKey;
+use zk_wasm::{body_hash, sha256_pad, verify_proof};
+
+/// `remaining_header` size of `bracu_verifier/src/main.nr`
+const NOIR_MAX_REMAINING: usize = 2048;
+/// Header size of `bracu_verifier.circom`
+const CIRCOM_MAX_HEADER: usize = 1024;
+
+const USAGE: &str = "\
+Usage: zkemail-inputs <command> [options]
+
+Commands:
+  inspect <email.eml>       DKIM tags, canonicalized signed header, split point
+                            and From indices
+      --key <file>          DKIM key (DNS TXT record or base64); required
+                            unless the key is pinned in keys/dkim_registry.json
+      --domain <d>          Use the signature with this d= (default: From domain)
+      --max-len <n>         Noir remaining_header size (default 2048)
+
+  inputs <email.eml>        Write circuit inputs as JSON (or Prover.toml)
+      --key <file>, --domain <d>
+      --circuit <noir|circom>   (default noir)
+      --max-len <n>         remaining_header (noir, default 2048) or header
+                            size (circom, default 1024)
+      --format <json|toml>  toml writes a nargo Prover.toml (noir only)
+      --out <file>          Output file (default stdout)
+
+  verify-dkim <email.eml>   Check the header signature and the body hash
+      --key <file>, --domain <d>
+
+  verify-proof              Check a Groth16 proof (snarkjs JSON or arkworks bytes)
+      --vk <file> --proof <file> --public <file>
+";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => code,
+        Err(CliError::Usage(message)) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    /// No key from `--key` or the registry
+    NoKey(String),
+    Io {
+        path: String,
+        error: std::io::Error,
+    },
+    ZkWasm(ZkWasmError),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Usage(message) | Self::NoKey(message) => write!(f, "error: {}", message),
+            Self::Io { path, error } => write!(f, "error: {}: {}", path, error),
+            Self::ZkWasm(e) => write!(f, "error[{}]: {}", e.code(), e),
+        }
+    }
+}
+
+impl From<ZkWasmError> for CliError {
+    fn from(e: ZkWasmError) -> Self {
+        Self::ZkWasm(e)
+    }
+}
+
+fn run(args: &[String]) -> Result<ExitCode, CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(CliError::Usage("missing command".to_string()));
+    };
+    match command.as_str() {
+        "inspect" => inspect(&Options::parse(rest, &["key", "domain", "max-len"])?),
+        "inputs" => inputs(&Options::parse(
+            rest,
+            &["key", "domain", "circuit", "max-len", "format", "out"],
+        )?),
+        "verify-dkim" => verify_dkim(&Options::parse(rest, &["key", "domain"])?),
+        "verify-proof" => verify_proof_files(&Options::parse(rest, &["vk", "proof", "public"])?),
+        "help" | "-h" | "--help" => {
+            print!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        other => Err(CliError::Usage(format!("unknown command '{}'", other))),
+    }
+}
+
+// ============================================================================
+// Options
+// ============================================================================
+
+/// Positional arguments and `--name value` (or `--name=value`) options
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    positional: Vec<String>,
+    flags: BTreeMap<String, String>,
+}
+
+impl Options {
+    fn parse(args: &[String], allowed: &[&str]) -> Result<Self, CliError> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                options.positional.push(arg.clone());
+                continue;
+            };
+            let (name, value) = match flag.split_once('=') {
+                Some((name, value)) => (name, value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| CliError::Usage(format!("--{} needs a value", flag)))?;
+                    (flag, value.clone())
+                }
+            };
+            if !allowed.contains(&name) {
+                return Err(CliError::Usage(format!("unknown option --{}", name)));
+            }
+            if options.flags.insert(name.to_string(), value).is_some() {
+                return Err(CliError::Usage(format!("--{} given twice", name)));
+            }
+        }
+        Ok(options)
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.flags.get(name).map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.get(name)
+            .ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
+    }
+
+    fn usize_or(&self, name: &str, default: usize) -> Result<usize, CliError> {
+        match self.get(name) {
+            Some(value) => value.parse().map_err(|_| {
+                CliError::Usage(format!("--{} must be a number, got '{}'", name, value))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    /// The single positional `.eml` path
+    fn email_path(&self) -> Result<&str, CliError> {
+        match self.positional.as_slice() {
+            [path] => Ok(path),
+            [] => Err(CliError::Usage("missing <email.eml>".to_string())),
+            _ => Err(CliError::Usage("expected a single <email.eml>".to_string())),
+        }
+    }
+
+    fn policy(&self) -> SignaturePolicy {
+        match self.get("domain") {
+            Some(domain) => SignaturePolicy::Domain(domain.to_string()),
+            None => SignaturePolicy::FromAligned,
+        }
+    }
+}
+
+fn read(path: &str) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|error| CliError::Io {
+        path: path.to_string(),
+        error,
+    })
+}
+
+/// The key from `--key` (and where it came from), else the pinned key for the
+/// signature's `d=`/`s=`; the inner `Err` says why there is neither
+fn resolve_key(
+    options: &Options,
+    signature: &DkimSignature,
+) -> Result<Result<(RsaPublicKey, String), String>, CliError> {
+    if let Some(path) = options.get("key") {
+        let record = String::from_utf8_lossy(&read(path)?).into_owned();
+        let key = parse_public_key(record.trim()).map_err(ZkWasmError::from)?;
+        return Ok(Ok((key, path.to_string())));
+    }
+    let registry = KeyRegistry::embedded().map_err(ZkWasmError::InvalidRegistry)?;
+    Ok(match registry.lookup_signature(signature) {
+        Some(entry) => Ok((
+            entry.public_key.clone(),
+            "embedded key registry".to_string(),
+        )),
+        None => Err(format!(
+            "no --key given and {}._domainkey.{} is not pinned in the embedded key \
+             registry; pass the DNS TXT record with --key or pin it in keys/dkim_registry.json",
+            signature.s, signature.d
+        )),
+    })
+}
+
+fn require_key(options: &Options, signature: &DkimSignature) -> Result<RsaPublicKey, CliError> {
+    resolve_key(options, signature)?
+        .map(|(key, _)| key)
+        .map_err(CliError::NoKey)
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+fn inspect(options: &Options) -> Result<ExitCode, CliError> {
+    let email = read(options.email_path()?)?;
+    let max_remaining = options.usize_or("max-len", NOIR_MAX_REMAINING)?;
+    let message = parse_message(&email);
+    let (selected, signature) =
+        dkim::select_signature(&message, &options.policy()).map_err(ZkWasmError::from)?;
+
+    for (number, (index, parsed)) in dkim::signature_fields(&message).into_iter().enumerate() {
+        let marker = if index == selected { " (selected)" } else { "" };
+        println!("DKIM-Signature {}{}", number + 1, marker);
+        match parsed {
+            Ok(signature) => print_tags(&signature),
+            Err(e) => println!("  invalid: {}", e),
+        }
+    }
+
+    let header = dkim::signed_header_data(&message, selected, &signature);
+    println!(
+        "\nSigned header ({} bytes, {} SHA-256 padded):",
+        header.len(),
+        sha256_pad(&header).len()
+    );
+    let mut offset = 0;
+    for line in header.split_inclusive(|b| *b == b'\n') {
+        let text = String::from_utf8_lossy(line)
+            .replace('\r', "\\r")
+            .replace('\n', "\\n");
+        println!("  [{:>5}] {}", offset, text);
+        offset += line.len();
+    }
+
+    match body_hash::verify_body_hash(&message, selected, &signature) {
+        Ok(body_hash) => println!(
+            "\nBody hash: ok (bh= at index {}, length {})",
+            body_hash.index, body_hash.length
+        ),
+        Err(e) => println!("\nBody hash: {}", e),
+    }
+
+    match precompute_header(&header, max_remaining) {
+        Ok(precomputed) => {
+            let partial = precomputed.partial_hash();
+            println!("\nSplit for {} remaining bytes:", max_remaining);
+            println!(
+                "  prehashed: {} bytes ({} blocks), remaining: {} bytes",
+                partial.prehashed_length(),
+                partial.prehashed_length() / 64,
+                partial.remaining().len()
+            );
+            println!(
+                "  from header:  index {}, length {}",
+                precomputed.from_header_index(),
+                precomputed.from_header_length()
+            );
+            println!(
+                "  from address: index {}, length {} ({})",
+                precomputed.from_address_index(),
+                precomputed.from_address_length(),
+                precomputed.from_email()
+            );
+            println!("  (indices are relative to the remaining bytes)");
+        }
+        Err(e) => println!(
+            "\nSplit for {} remaining bytes: error[{}]: {}",
+            max_remaining,
+            e.code(),
+            e
+        ),
+    }
+
+    match resolve_key(options, &signature)? {
+        Ok((key, source)) => {
+            match dkim::verify_signature_at(&message, selected, &signature, &key) {
+                Ok(()) => println!("\nDKIM signature: verified with {}", source),
+                Err(e) => println!(
+                    "\nDKIM signature: fails with {} ({}): {}",
+                    source,
+                    e.step(),
+                    e
+                ),
+            }
+        }
+        Err(reason) => println!("\nDKIM signature: not checked ({})", reason),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_tags(signature: &DkimSignature) {
+    println!("  v={} a={} c={}", signature.v, signature.a, signature.c);
+    println!("  d={} s={}", signature.d, signature.s);
+    println!("  h={}", signature.h.join(":"));
+    println!("  bh={}", signature.bh);
+    println!(
+        "  b={}... ({} base64 chars)",
+        signature.b.chars().take(16).collect::<String>(),
+        signature.b.len()
+    );
+    let optional = [
+        ("t", signature.t.map(|t| t.to_string())),
+        ("x", signature.x.map(|x| x.to_string())),
+        ("l", signature.l.map(|l| l.to_string())),
+        ("i", signature.i.clone()),
+        ("q", signature.q.clone()),
+    ];
+    for (tag, value) in optional {
+        if let Some(value) = value {
+            println!("  {}={}", tag, value);
+        }
+    }
+}
+
+fn inputs(options: &Options) -> Result<ExitCode, CliError> {
+    let email = read(options.email_path()?)?;
+    let policy = options.policy();
+    let (_, signature) =
+        dkim::select_signature(&parse_message(&email), &policy).map_err(ZkWasmError::from)?;
+    let key = require_key(options, &signature)?;
+
+    let format = options.get("format").unwrap_or("json");
+    let output = match (options.get("circuit").unwrap_or("noir"), format) {
+        ("noir", "json" | "toml") => {
+            let max = options.usize_or("max-len", NOIR_MAX_REMAINING)?;
+            let inputs = noir_inputs(&email, &key, &policy, max)?;
+            match format {
+                "toml" => inputs.to_prover_toml(),
+                _ => pretty(&inputs.to_json_value()),
+            }
+        }
+        ("circom", "json") => {
+            let max = options.usize_or("max-len", CIRCOM_MAX_HEADER)?;
+            pretty(&circom_inputs(&email, &key, &policy, max)?.to_json())
+        }
+        ("circom", other) => {
+            return Err(CliError::Usage(format!(
+                "--format {} is not available for circom",
+                other
+            )))
+        }
+        ("noir", other) => return Err(CliError::Usage(format!("unknown --format '{}'", other))),
+        (other, _) => return Err(CliError::Usage(format!("unknown --circuit '{}'", other))),
+    };
+
+    match options.get("out") {
+        Some(path) => {
+            fs::write(path, output).map_err(|error| CliError::Io {
+                path: path.to_string(),
+                error,
+            })?;
+            eprintln!("Wrote {}", path);
+        }
+        None => writeln!(std::io::stdout(), "{}", output).map_err(|error| CliError::Io {
+            path: "<stdout>".to_string(),
+            error,
+        })?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).expect("JSON values always serialize")
+}
+
+fn verify_dkim(options: &Options) -> Result<ExitCode, CliError> {
+    let email = read(options.email_path()?)?;
+    let message = parse_message(&email);
+    let (index, signature) =
+        dkim::select_signature(&message, &options.policy()).map_err(ZkWasmError::from)?;
+    let key = require_key(options, &signature)?;
+
+    let header = dkim::verify_signature_at(&message, index, &signature, &key);
+    let body = body_hash::verify_body_hash(&message, index, &signature);
+    println!("d={} s={}", signature.d, signature.s);
+    match &header {
+        Ok(()) => println!("header signature: valid"),
+        Err(e) => println!("header signature: invalid ({}): {}", e.step(), e),
+    }
+    match &body {
+        Ok(_) => println!("body hash: valid"),
+        Err(e) => println!("body hash: invalid: {}", e),
+    }
+    Ok(if header.is_ok() && body.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn verify_proof_files(options: &Options) -> Result<ExitCode, CliError> {
+    if !options.positional.is_empty() {
+        return Err(CliError::Usage(
+            "verify-proof takes no positional arguments".to_string(),
+        ));
+    }
+    let vk = read(options.required("vk")?)?;
+    let proof = read(options.required("proof")?)?;
+    let public = read(options.required("public")?)?;
+
+    let result = verify_proof(&vk, &proof, &public);
+    match result.reason() {
+        None => {
+            println!("proof: valid");
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(reason) => {
+            println!("proof: invalid: {}", reason);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_options() {
+        let options = Options::parse(
+            &args(&["mail.eml", "--key", "key.txt", "--max-len=1024"]),
+            &["key", "max-len"],
+        )
+        .unwrap();
+        assert_eq!(options.email_path().unwrap(), "mail.eml");
+        assert_eq!(options.get("key"), Some("key.txt"));
+        assert_eq!(options.usize_or("max-len", 2048).unwrap(), 1024);
+        assert_eq!(options.usize_or("other", 7).unwrap(), 7);
+        assert!(matches!(options.policy(), SignaturePolicy::FromAligned));
+
+        let usage = |list: &[&str]| {
+            matches!(
+                Options::parse(&args(list), &["key"]),
+                Err(CliError::Usage(_))
+            )
+        };
+        assert!(usage(&["--key"]));
+        assert!(usage(&["--unknown", "x"]));
+        assert!(usage(&["--key", "a", "--key", "b"]));
+        assert!(matches!(
+            run(&args(&["frobnicate"])),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(run(&args(&["inputs"])), Err(CliError::Usage(_))));
+    }
+
+    const EMAIL: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/dkim_test_email.eml");
+    const KEY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/dkim_test_record.txt");
+
+    /// A fresh path under the temp directory for this test
+    fn temp_path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("zkemail-inputs-{}-{}", std::process::id(), name));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            run(&args(&["inspect", EMAIL, "--key", KEY])).unwrap(),
+            ExitCode::SUCCESS
+        );
+        assert_eq!(
+            run(&args(&["verify-dkim", EMAIL, "--key", KEY])).unwrap(),
+            ExitCode::SUCCESS
+        );
+        assert!(matches!(
+            run(&args(&["verify-dkim", EMAIL])),
+            Err(CliError::NoKey(_))
+        ));
+
+        let out = temp_path("inputs.json");
+        assert_eq!(
+            run(&args(&["inputs", EMAIL, "--key", KEY, "--out", &out])).unwrap(),
+            ExitCode::SUCCESS
+        );
+        let inputs: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert!(inputs.as_object().is_some_and(|inputs| !inputs.is_empty()));
+        let toml = temp_path("Prover.toml");
+        assert_eq!(
+            run(&args(&[
+                "inputs", EMAIL, "--key", KEY, "--format", "toml", "--out", &toml
+            ]))
+            .unwrap(),
+            ExitCode::SUCCESS
+        );
+        let circom = temp_path("circom.json");
+        assert_eq!(
+            run(&args(&[
+                "inputs",
+                EMAIL,
+                "--key",
+                KEY,
+                "--circuit",
+                "circom",
+                "--out",
+                &circom
+            ]))
+            .unwrap(),
+            ExitCode::SUCCESS
+        );
+        let circom: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&circom).unwrap()).unwrap();
+        assert!(circom.get("in_padded").is_some());
+
+        // A tampered body still parses but fails verification
+        let tampered = temp_path("tampered.eml");
+        let email = fs::read_to_string(EMAIL)
+            .unwrap()
+            .replace("Hello Bob", "Hello Eve");
+        fs::write(&tampered, email).unwrap();
+        assert_eq!(
+            run(&args(&["verify-dkim", &tampered, "--key", KEY])).unwrap(),
+            ExitCode::FAILURE
+        );
+
+        // A non-ASCII b= is printed by character, not by byte
+        let garbled = temp_path("garbled.eml");
+        let email = fs::read_to_string(EMAIL)
+            .unwrap()
+            .replacen("\tb=", "\tb=ééééééééé", 1);
+        fs::write(&garbled, email).unwrap();
+        assert_eq!(
+            run(&args(&["inspect", &garbled, "--key", KEY])).unwrap(),
+            ExitCode::SUCCESS
+        );
+
+        for path in [out, toml, tampered, garbled] {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
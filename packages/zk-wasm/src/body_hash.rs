@@ -0,0 +1,262 @@
+// ============================================================================
+// DKIM body hash (bh=) verification (RFC 6376 §3.7)
+// ============================================================================
+
+use crate::canonicalization::{canonicalize_body, field_end, ParsedMessage};
+use crate::dkim::{signed_header_data, DkimSignature};
+use crate::sha256;
+use base64::{engine::general_purpose, Engine as _};
+use std::fmt;
+
+/// Why the body hash check failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BodyHashError {
+    /// `l=` claims more bytes than the canonicalized body has
+    LengthExceedsBody { limit: u64, body_length: usize },
+    /// The body does not hash to `bh=`
+    Mismatch { expected: String, computed: String },
+    /// `bh=` could not be located in the signed header data
+    TagNotFound,
+}
+
+impl fmt::Display for BodyHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthExceedsBody { limit, body_length } => write!(
+                f,
+                "Body length limit l={} exceeds the {}-byte canonicalized body",
+                limit, body_length
+            ),
+            Self::Mismatch { expected, computed } => write!(
+                f,
+                "Body hash mismatch: bh={} but the body hashes to {}",
+                expected, computed
+            ),
+            Self::TagNotFound => write!(f, "Could not find bh= in the signed header data"),
+        }
+    }
+}
+
+impl std::error::Error for BodyHashError {}
+
+/// A verified body hash and where its base64 value sits in the signed header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BodyHash {
+    /// Base64 SHA-256 of the canonicalized (and `l=`-truncated) body
+    pub hash: String,
+    /// Offset of the `bh=` value within the canonicalized signed header data
+    pub index: usize,
+    /// Length of the raw `bh=` value in bytes, including any folding whitespace
+    pub length: usize,
+}
+
+/// Canonicalized body bytes covered by the signature, honouring `l=`
+pub fn signed_body(
+    message: &ParsedMessage,
+    signature: &DkimSignature,
+) -> Result<Vec<u8>, BodyHashError> {
+    let (_, body_algorithm) = signature.canonicalization();
+    let mut body = canonicalize_body(&message.body, body_algorithm);
+    if let Some(limit) = signature.l {
+        if limit > body.len() as u64 {
+            return Err(BodyHashError::LengthExceedsBody {
+                limit,
+                body_length: body.len(),
+            });
+        }
+        body.truncate(limit as usize);
+    }
+    Ok(body)
+}
+
+/// Check the body of `message` against `bh=` of the signature at `field_index`
+pub fn verify_body_hash(
+    message: &ParsedMessage,
+    field_index: usize,
+    signature: &DkimSignature,
+) -> Result<BodyHash, BodyHashError> {
+    let hash = general_purpose::STANDARD.encode(sha256(&signed_body(message, signature)?));
+    if hash != signature.bh {
+        return Err(BodyHashError::Mismatch {
+            expected: signature.bh.clone(),
+            computed: hash,
+        });
+    }
+
+    let header = signed_header_data(message, field_index, signature);
+    let (index, length) =
+        body_hash_index(&header, &signature.bh).ok_or(BodyHashError::TagNotFound)?;
+    Ok(BodyHash {
+        hash,
+        index,
+        length,
+    })
+}
+
+/// Offset and length of the raw `bh=` value in canonicalized header data.
+///
+/// The DKIM-Signature is the last field, so the search starts after its
+/// colon (its name may keep WSP before the colon under `c=simple`) and only
+/// accepts `bh=` at a tag boundary. `bh` is the value without
+/// whitespace; folding whitespace around `=` and inside the raw value is
+/// skipped while matching and counted in the returned length.
+pub fn body_hash_index(header: &[u8], bh: &str) -> Option<(usize, usize)> {
+    let mut start = 0;
+    loop {
+        let end = field_end(header, start);
+        if end >= header.len() {
+            break;
+        }
+        start = end + 2;
+    }
+    let colon = start + header[start..].iter().position(|b| *b == b':')?;
+    let name = String::from_utf8_lossy(&header[start..colon]);
+    if !name.trim_end().eq_ignore_ascii_case("DKIM-Signature") {
+        return None;
+    }
+
+    let lower = header.to_ascii_lowercase();
+    let mut from = colon + 1;
+    while let Some(pos) = lower[from..].windows(2).position(|w| w == b"bh") {
+        let tag = from + pos;
+        from = tag + 2;
+        if !matches!(header[tag - 1], b';' | b':' | b' ' | b'\t' | b'\n') {
+            continue;
+        }
+        let eq = skip_fws(header, tag + 2);
+        if header.get(eq) != Some(&b'=') {
+            continue;
+        }
+        let value = skip_fws(header, eq + 1);
+        if let Some(end) = match_skipping_fws(header, value, bh.as_bytes()) {
+            return Some((value, end - value));
+        }
+    }
+    None
+}
+
+fn skip_fws(header: &[u8], mut i: usize) -> usize {
+    while header
+        .get(i)
+        .is_some_and(|b| matches!(b, b' ' | b'\t' | b'\r' | b'\n'))
+    {
+        i += 1;
+    }
+    i
+}
+
+/// End of `expected` at `start` when whitespace between its bytes is ignored
+fn match_skipping_fws(header: &[u8], start: usize, expected: &[u8]) -> Option<usize> {
+    let mut i = start;
+    for (n, byte) in expected.iter().enumerate() {
+        if n > 0 {
+            i = skip_fws(header, i);
+        }
+        if header.get(i) != Some(byte) {
+            return None;
+        }
+        i += 1;
+    }
+    // The value must end the tag
+    matches!(header.get(skip_fws(header, i)), None | Some(b';')).then_some(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonicalization::parse_message;
+    use crate::dkim::{select_signature, tests::TEST_EMAIL, SignaturePolicy};
+
+    #[test]
+    fn test_verify_body_hash() {
+        let message = parse_message(TEST_EMAIL.as_bytes());
+        let (index, signature) = select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+        let body_hash = verify_body_hash(&message, index, &signature).unwrap();
+        assert_eq!(body_hash.hash, signature.bh);
+
+        let header = signed_header_data(&message, index, &signature);
+        assert_eq!(
+            &header[body_hash.index..body_hash.index + body_hash.length],
+            signature.bh.as_bytes()
+        );
+
+        let tampered = parse_message(TEST_EMAIL.replace("Hello Bob", "Hello Eve").as_bytes());
+        assert!(matches!(
+            verify_body_hash(&tampered, index, &signature),
+            Err(BodyHashError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_body_length_limit() {
+        // l=11 covers "Hello Bob\r\n"; anything appended afterwards is unsigned
+        let email = TEST_EMAIL.replace("c=relaxed/relaxed;", "c=relaxed/relaxed; l=11;");
+        let appended = format!("{}P.S. unsigned\r\n", email);
+        let message = parse_message(appended.as_bytes());
+        let (index, signature) = select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+        assert!(verify_body_hash(&message, index, &signature).is_ok());
+
+        let too_long = email.replace("l=11;", "l=4096;");
+        let message = parse_message(too_long.as_bytes());
+        let (index, signature) = select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+        assert_eq!(
+            verify_body_hash(&message, index, &signature),
+            Err(BodyHashError::LengthExceedsBody {
+                limit: 4096,
+                body_length: 11
+            })
+        );
+    }
+
+    #[test]
+    fn test_body_hash_index_skips_other_tags() {
+        let header = b"from:a@x.com\r\ndkim-signature:v=1; xbh=QUFB; bh=QUFB; b=";
+        assert_eq!(body_hash_index(header, "QUFB"), Some((47, 4)));
+        assert_eq!(body_hash_index(header, "QUF"), None);
+        // Only the last field is searched
+        let header = b"dkim-signature:bh=QUFB\r\nfrom:a@x.com";
+        assert_eq!(body_hash_index(header, "QUFB"), None);
+    }
+
+    #[test]
+    fn test_body_hash_index_after_spaced_name() {
+        let email = TEST_EMAIL
+            .replace("c=relaxed/relaxed", "c=simple/simple")
+            .replace("DKIM-Signature:", "DKIM-Signature :");
+        let message = parse_message(email.as_bytes());
+        let (index, signature) = select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+        let body_hash = verify_body_hash(&message, index, &signature).unwrap();
+
+        let header = signed_header_data(&message, index, &signature);
+        assert!(header.windows(16).any(|w| w == b"DKIM-Signature :"));
+        assert_eq!(
+            &header[body_hash.index..body_hash.index + body_hash.length],
+            signature.bh.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_folded_body_hash() {
+        let folded = TEST_EMAIL.replace(
+            "bh=YJC6sFHGk5paEfivUv71xLRk9l6/Mpywbes6g++OLys=;",
+            "bh = YJC6sFHGk5paEfivUv71\r\n\t xLRk9l6/Mpywbes6g++OLys=;",
+        );
+        for c in ["c=relaxed/relaxed", "c=simple/simple"] {
+            let email = folded.replace("c=relaxed/relaxed", c);
+            let message = parse_message(email.as_bytes());
+            let (index, signature) =
+                select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+            let body_hash = verify_body_hash(&message, index, &signature).unwrap();
+
+            let header = signed_header_data(&message, index, &signature);
+            let raw = &header[body_hash.index..body_hash.index + body_hash.length];
+            let unfolded: Vec<u8> = raw
+                .iter()
+                .copied()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect();
+            assert_eq!(unfolded, signature.bh.as_bytes(), "{}", c);
+            assert!(raw.len() > signature.bh.len(), "{}", c);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+// ============================================================================
+// RFC 6376 §3.4 header and body canonicalization
+// ============================================================================
+
+/// DKIM canonicalization algorithm (`simple` or `relaxed`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Canonicalization {
+    Simple,
+    Relaxed,
+}
+
+impl Canonicalization {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "simple" => Ok(Self::Simple),
+            "relaxed" => Ok(Self::Relaxed),
+            other => Err(format!("Unknown canonicalization algorithm '{}'", other)),
+        }
+    }
+
+    /// Parse a `c=` tag value into `(header, body)` algorithms.
+    ///
+    /// A missing body algorithm defaults to `simple`, as does an absent tag.
+    pub fn parse_tag(value: Option<&str>) -> Result<(Self, Self), String> {
+        let value = match value {
+            Some(v) if !v.trim().is_empty() => v,
+            _ => return Ok((Self::Simple, Self::Simple)),
+        };
+        match value.split_once('/') {
+            Some((header, body)) => Ok((Self::parse(header)?, Self::parse(body)?)),
+            None => Ok((Self::parse(value)?, Self::Simple)),
+        }
+    }
+}
+
+/// A raw header field, including folding whitespace and the final CRLF
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderField {
+    /// Field name as written (case preserved)
+    pub name: String,
+    /// Complete field bytes: `name:value\r\n` with any continuation lines
+    pub raw: Vec<u8>,
+    /// Offset of the field within the normalized message
+    pub offset: usize,
+}
+
+impl HeaderField {
+    /// Field value after the colon, still folded
+    pub fn value(&self) -> String {
+        let raw = String::from_utf8_lossy(&self.raw);
+        raw.split_once(':')
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Message split into its header fields and body
+#[derive(Clone, Debug)]
+pub struct ParsedMessage {
+    pub headers: Vec<HeaderField>,
+    pub body: Vec<u8>,
+}
+
+/// Convert bare LF line endings to CRLF, as they were on the wire
+pub fn normalize_line_endings(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 32);
+    for (i, &byte) in data.iter().enumerate() {
+        if byte == b'\n' && (i == 0 || data[i - 1] != b'\r') {
+            out.push(b'\r');
+        }
+        out.push(byte);
+    }
+    out
+}
+
+/// Split a message (after line-ending normalization) into header fields and body
+pub fn parse_message(email: &[u8]) -> ParsedMessage {
+    let data = normalize_line_endings(email);
+    let mut headers: Vec<HeaderField> = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let line_end = find_crlf(&data, pos).map(|i| i + 2).unwrap_or(data.len());
+        let line = &data[pos..line_end];
+
+        if line == b"\r\n" {
+            // Empty line separates headers from body
+            pos = line_end;
+            return ParsedMessage {
+                headers,
+                body: data[pos..].to_vec(),
+            };
+        }
+
+        match (line[0] == b' ' || line[0] == b'\t', headers.last_mut()) {
+            (true, Some(field)) => field.raw.extend_from_slice(line),
+            _ => {
+                let name_end = line.iter().position(|&b| b == b':').unwrap_or(line.len());
+                headers.push(HeaderField {
+                    name: String::from_utf8_lossy(&line[..name_end]).trim_end().to_string(),
+                    raw: line.to_vec(),
+                    offset: pos,
+                });
+            }
+        }
+        pos = line_end;
+    }
+
+    ParsedMessage {
+        headers,
+        body: Vec::new(),
+    }
+}
+
+fn find_crlf(data: &[u8], from: usize) -> Option<usize> {
+    data[from..]
+        .windows(2)
+        .position(|w| w == b"\r\n")
+        .map(|i| i + from)
+}
+
+fn is_wsp(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+/// End of the header field starting at `from` in CRLF-separated header data:
+/// the first CRLF not followed by WSP (a fold), or the end of `data`
+pub(crate) fn field_end(data: &[u8], from: usize) -> usize {
+    let mut pos = from;
+    while let Some(crlf) = find_crlf(data, pos) {
+        if !data.get(crlf + 2).is_some_and(|&b| is_wsp(b)) {
+            return crlf;
+        }
+        pos = crlf + 2;
+    }
+    data.len()
+}
+
+/// Canonicalize a single header field (`raw` includes its trailing CRLF)
+pub fn canonicalize_header(raw: &[u8], algorithm: Canonicalization) -> Vec<u8> {
+    match algorithm {
+        Canonicalization::Simple => raw.to_vec(),
+        Canonicalization::Relaxed => {
+            let colon = raw.iter().position(|&b| b == b':').unwrap_or(raw.len());
+            let name = &raw[..colon];
+            let value = raw.get(colon + 1..).unwrap_or_default();
+
+            let mut out: Vec<u8> = name
+                .iter()
+                .copied()
+                .filter(|b| !is_wsp(*b))
+                .map(|b| b.to_ascii_lowercase())
+                .collect();
+            out.push(b':');
+
+            // Unfold, then collapse whitespace runs and trim both ends
+            let unfolded: Vec<u8> = value
+                .iter()
+                .copied()
+                .filter(|b| *b != b'\r' && *b != b'\n')
+                .collect();
+            let (mut started, mut pending_space) = (false, false);
+            for byte in unfolded {
+                if is_wsp(byte) {
+                    pending_space = true;
+                    continue;
+                }
+                if pending_space && started {
+                    out.push(b' ');
+                }
+                started = true;
+                pending_space = false;
+                out.push(byte);
+            }
+            out.extend_from_slice(b"\r\n");
+            out
+        }
+    }
+}
+
+/// Canonicalize a message body
+pub fn canonicalize_body(body: &[u8], algorithm: Canonicalization) -> Vec<u8> {
+    let body = normalize_line_endings(body);
+    let mut lines: Vec<Vec<u8>> = body
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line).to_vec())
+        .collect();
+    // `split` yields a trailing empty element after the final CRLF
+    if body.ends_with(b"\n") {
+        lines.pop();
+    }
+
+    if algorithm == Canonicalization::Relaxed {
+        for line in lines.iter_mut() {
+            let mut reduced = Vec::with_capacity(line.len());
+            let mut pending_space = false;
+            for &byte in line.iter() {
+                if is_wsp(byte) {
+                    pending_space = true;
+                    continue;
+                }
+                if pending_space {
+                    reduced.push(b' ');
+                }
+                pending_space = false;
+                reduced.push(byte);
+            }
+            *line = reduced;
+        }
+    }
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    if lines.is_empty() {
+        // An empty body is "\r\n" under simple and empty under relaxed
+        return match algorithm {
+            Canonicalization::Simple => b"\r\n".to_vec(),
+            Canonicalization::Relaxed => Vec::new(),
+        };
+    }
+
+    let mut out = Vec::with_capacity(body.len());
+    for line in lines {
+        out.extend_from_slice(&line);
+        out.extend_from_slice(b"\r\n");
+    }
+    out
+}
+
+/// Remove the value of the `b=` tag from a raw DKIM-Signature field, keeping
+/// every other byte (including `bh=` and folding whitespace) intact
+pub fn strip_signature_value(raw: &[u8]) -> Vec<u8> {
+    let colon = match raw.iter().position(|&b| b == b':') {
+        Some(i) => i,
+        None => return raw.to_vec(),
+    };
+    let mut out = raw[..=colon].to_vec();
+    let value = &raw[colon + 1..];
+    let mut first = true;
+
+    for tag in value.split(|&b| b == b';') {
+        if !first {
+            out.push(b';');
+        }
+        first = false;
+
+        let name_start = tag.iter().position(|b| !b.is_ascii_whitespace());
+        let eq = tag.iter().position(|&b| b == b'=');
+        let is_b_tag = match (name_start, eq) {
+            (Some(start), Some(eq)) if start < eq => {
+                tag[start..eq].iter().filter(|b| !b.is_ascii_whitespace()).eq(b"b".iter())
+            }
+            _ => false,
+        };
+
+        if is_b_tag {
+            let eq = eq.unwrap_or_default();
+            out.extend_from_slice(&tag[..=eq]);
+            // Keep the line terminator of the last tag in the field
+            if tag.ends_with(b"\r\n") {
+                out.extend_from_slice(b"\r\n");
+            }
+        } else {
+            out.extend_from_slice(tag);
+        }
+    }
+    out
+}
+
+/// Select the header fields listed in `h=` (bottom-up for repeated names).
+///
+/// Names with no remaining instance are skipped, as RFC 6376 §5.4.2 requires.
+pub fn select_signed_headers<'a>(
+    headers: &'a [HeaderField],
+    signed_headers: &[&str],
+) -> Vec<&'a HeaderField> {
+    let mut used = vec![false; headers.len()];
+    let mut selected = Vec::new();
+
+    for name in signed_headers {
+        let name = name.trim();
+        let found = headers
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(i, field)| !used[*i] && field.name.eq_ignore_ascii_case(name));
+        if let Some((i, field)) = found {
+            used[i] = true;
+            selected.push(field);
+        }
+    }
+    selected
+}
+
+/// Build the exact byte string hashed by the signer: the `h=` headers in
+/// order, each canonicalized, followed by the DKIM-Signature field itself
+/// with an empty `b=` and no trailing CRLF.
+pub fn canonicalized_signed_data(
+    headers: &[HeaderField],
+    dkim_field: &HeaderField,
+    signed_headers: &[&str],
+    algorithm: Canonicalization,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    for field in select_signed_headers(headers, signed_headers) {
+        out.extend_from_slice(&canonicalize_header(&field.raw, algorithm));
+    }
+
+    let signature = canonicalize_header(&strip_signature_value(&dkim_field.raw), algorithm);
+    out.extend_from_slice(signature.strip_suffix(b"\r\n").unwrap_or(&signature));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example message from RFC 6376 §3.4.5
+    const RFC_EXAMPLE: &[u8] =
+        b"A: X\r\nB : Y\t\r\n\tZ  \r\n\r\n C \r\nD \t E\r\n\r\n\r\n";
+
+    #[test]
+    fn test_rfc6376_example() {
+        let message = parse_message(RFC_EXAMPLE);
+        assert_eq!(message.headers.len(), 2);
+
+        let relaxed: Vec<u8> = message
+            .headers
+            .iter()
+            .flat_map(|h| canonicalize_header(&h.raw, Canonicalization::Relaxed))
+            .collect();
+        assert_eq!(relaxed, b"a:X\r\nb:Y Z\r\n");
+        assert_eq!(
+            canonicalize_header(b"Subject :  Re: \t hi \r\n", Canonicalization::Relaxed),
+            b"subject:Re: hi\r\n"
+        );
+        assert_eq!(
+            canonicalize_body(&message.body, Canonicalization::Relaxed),
+            b" C\r\nD E\r\n"
+        );
+
+        let simple: Vec<u8> = message
+            .headers
+            .iter()
+            .flat_map(|h| canonicalize_header(&h.raw, Canonicalization::Simple))
+            .collect();
+        assert_eq!(simple, b"A: X\r\nB : Y\t\r\n\tZ  \r\n");
+        assert_eq!(
+            canonicalize_body(&message.body, Canonicalization::Simple),
+            b" C \r\nD \t E\r\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_body() {
+        assert_eq!(canonicalize_body(b"", Canonicalization::Simple), b"\r\n");
+        assert_eq!(canonicalize_body(b"\r\n\r\n", Canonicalization::Simple), b"\r\n");
+        assert_eq!(canonicalize_body(b"", Canonicalization::Relaxed), b"");
+    }
+
+    #[test]
+    fn test_parse_c_tag() {
+        use Canonicalization::*;
+        assert_eq!(Canonicalization::parse_tag(None).unwrap(), (Simple, Simple));
+        assert_eq!(Canonicalization::parse_tag(Some("relaxed")).unwrap(), (Relaxed, Simple));
+        assert_eq!(Canonicalization::parse_tag(Some("relaxed/relaxed")).unwrap(), (Relaxed, Relaxed));
+        assert!(Canonicalization::parse_tag(Some("nowsp")).is_err());
+    }
+
+    #[test]
+    fn test_signed_data_selection_and_empty_b() {
+        let email = b"From: a@x.com\nTo: b@y.com\nSubject: one\nSubject: two\n\
+DKIM-Signature: v=1; a=rsa-sha256; c=relaxed/relaxed; d=x.com; s=sel;\n\
+\th=from:subject:subject:subject; bh=AAAA; b=abc\n\tdef==\n\nbody\n";
+        let message = parse_message(email);
+        let dkim = message
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case("DKIM-Signature"))
+            .unwrap();
+
+        let data = canonicalized_signed_data(
+            &message.headers,
+            dkim,
+            &["from", "subject", "subject", "subject"],
+            Canonicalization::Relaxed,
+        );
+        let expected = "from:a@x.com\r\nsubject:two\r\nsubject:one\r\n\
+dkim-signature:v=1; a=rsa-sha256; c=relaxed/relaxed; d=x.com; s=sel; h=from:subject:subject:subject; bh=AAAA; b=";
+        assert_eq!(String::from_utf8(data).unwrap(), expected);
+    }
+}
@@ -0,0 +1,649 @@
+// ============================================================================
+// Circom circuit artifacts: R1CS binary format and WASM witness generator
+// ============================================================================
+
+use ark_bn254::Fr;
+use ark_ff::{One, PrimeField, Zero};
+use num_bigint::BigUint;
+use wasmi::core::Trap;
+use wasmi::{Caller, Engine, Extern, Instance, Linker, Module, Store};
+
+/// Sparse linear combination: `(wire index, coefficient)` pairs
+pub type LinearCombination = Vec<(usize, Fr)>;
+
+/// A single `A * B = C` constraint
+pub type Constraint = (LinearCombination, LinearCombination, LinearCombination);
+
+// R1CS section types
+const R1CS_SECTION_HEADER: u32 = 1;
+const R1CS_SECTION_CONSTRAINTS: u32 = 2;
+const R1CS_SECTION_CUSTOM_GATES_LIST: u32 = 4;
+const R1CS_SECTION_CUSTOM_GATES_USES: u32 = 5;
+
+/// Constraint system parsed from a circom `.r1cs` file
+#[derive(Clone, Debug)]
+pub struct R1CS {
+    /// Total number of wires (witness size), including the constant `1`
+    pub num_wires: usize,
+    /// Number of public outputs
+    pub num_pub_out: usize,
+    /// Number of public inputs
+    pub num_pub_in: usize,
+    /// Number of private inputs
+    pub num_prv_in: usize,
+    /// Constraints in file order
+    pub constraints: Vec<Constraint>,
+}
+
+impl R1CS {
+    /// Number of instance variables, including the constant `1` at index 0
+    pub fn num_instance(&self) -> usize {
+        1 + self.num_pub_out + self.num_pub_in
+    }
+
+    /// Number of private witness variables
+    pub fn num_aux(&self) -> usize {
+        self.num_wires - self.num_instance()
+    }
+
+    /// Check that a full witness satisfies every constraint.
+    ///
+    /// On failure the error names the index of the first unsatisfied constraint.
+    pub fn check_witness(&self, witness: &[Fr]) -> Result<(), String> {
+        if witness.len() != self.num_wires {
+            return Err(format!(
+                "Witness has {} values but the circuit has {} wires",
+                witness.len(),
+                self.num_wires
+            ));
+        }
+        if !witness[0].is_one() {
+            return Err("Witness must start with the constant 1".to_string());
+        }
+
+        let eval = |terms: &LinearCombination| -> Result<Fr, String> {
+            terms.iter().try_fold(Fr::zero(), |acc, (wire, coeff)| {
+                witness
+                    .get(*wire)
+                    .map(|value| acc + *coeff * value)
+                    .ok_or_else(|| format!("Constraint references unknown wire {}", wire))
+            })
+        };
+
+        for (index, (a, b, c)) in self.constraints.iter().enumerate() {
+            if eval(a)? * eval(b)? != eval(c)? {
+                return Err(format!("Constraint {} is not satisfied by the witness", index));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Little-endian reader over the `.r1cs` / `.zkey` binary containers
+pub(crate) struct BinReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("Unexpected end of file at offset {}", self.pos))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, String> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, String> {
+        let b = self.bytes(8)?;
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(b);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    pub(crate) fn biguint(&mut self, n8: usize) -> Result<BigUint, String> {
+        Ok(BigUint::from_bytes_le(self.bytes(n8)?))
+    }
+
+    /// Bytes left to read
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+}
+
+/// Split a circom binary container (`r1cs`, `zkey`, `wtns`) into its sections.
+///
+/// Returns `(section type, section bytes)` pairs in file order.
+pub(crate) fn read_sections<'a>(
+    data: &'a [u8],
+    magic: &[u8; 4],
+) -> Result<Vec<(u32, &'a [u8])>, String> {
+    let mut reader = BinReader::new(data);
+    if reader.bytes(4)? != magic {
+        return Err(format!(
+            "Invalid file: expected '{}' magic",
+            String::from_utf8_lossy(magic)
+        ));
+    }
+    let _version = reader.u32()?;
+    let num_sections = reader.u32()?;
+
+    // Counts come from the file, so capacities are capped by the bytes left:
+    // every section needs at least its 12-byte type and size
+    let mut sections = Vec::with_capacity((num_sections as usize).min(reader.remaining() / 12));
+    for _ in 0..num_sections {
+        let section_type = reader.u32()?;
+        let size = usize::try_from(reader.u64()?)
+            .map_err(|_| format!("Section {} is too large for this platform", section_type))?;
+        sections.push((section_type, reader.bytes(size)?));
+    }
+    Ok(sections)
+}
+
+/// Parse a circom `.r1cs` binary file
+pub fn parse_r1cs(data: &[u8]) -> Result<R1CS, String> {
+    let sections = read_sections(data, b"r1cs")?;
+    let find = |ty: u32| {
+        sections
+            .iter()
+            .find(|(t, _)| *t == ty)
+            .map(|(_, s)| *s)
+            .ok_or_else(|| format!("R1CS file is missing section {}", ty))
+    };
+
+    if sections.iter().any(|(t, _)| {
+        *t == R1CS_SECTION_CUSTOM_GATES_LIST || *t == R1CS_SECTION_CUSTOM_GATES_USES
+    }) {
+        return Err("R1CS files with custom gates (PLONK) are not supported".to_string());
+    }
+
+    let mut header = BinReader::new(find(R1CS_SECTION_HEADER)?);
+    let n8 = header.u32()? as usize;
+    check_prime(&header.biguint(n8)?)?;
+    let num_wires = header.u32()? as usize;
+    let num_pub_out = header.u32()? as usize;
+    let num_pub_in = header.u32()? as usize;
+    let num_prv_in = header.u32()? as usize;
+    let _num_labels = header.u64()?;
+    let num_constraints = header.u32()? as usize;
+
+    let mut body = BinReader::new(find(R1CS_SECTION_CONSTRAINTS)?);
+    let read_lc = |reader: &mut BinReader| -> Result<LinearCombination, String> {
+        let num_terms = reader.u32()? as usize;
+        (0..num_terms)
+            .map(|_| {
+                let wire = reader.u32()? as usize;
+                let coeff = Fr::from(reader.biguint(n8)?);
+                Ok((wire, coeff))
+            })
+            .collect()
+    };
+
+    // Each constraint holds at least three 4-byte term counts
+    let mut constraints = Vec::with_capacity(num_constraints.min(body.remaining() / 12));
+    for _ in 0..num_constraints {
+        let a = read_lc(&mut body)?;
+        let b = read_lc(&mut body)?;
+        let c = read_lc(&mut body)?;
+        constraints.push((a, b, c));
+    }
+
+    if num_wires < 1 + num_pub_out + num_pub_in + num_prv_in {
+        return Err(format!("R1CS header declares too few wires ({})", num_wires));
+    }
+
+    Ok(R1CS {
+        num_wires,
+        num_pub_out,
+        num_pub_in,
+        num_prv_in,
+        constraints,
+    })
+}
+
+/// Host-side state shared with the witness WASM instance
+#[derive(Default)]
+struct HostState {
+    /// Messages emitted by the circuit via `printErrorMessage` / `writeBufferMessage`
+    messages: Vec<String>,
+}
+
+/// Witness generator backed by the circom-compiled `.wasm` module
+pub struct WitnessCalculator {
+    store: Store<HostState>,
+    instance: Instance,
+    /// Number of 32-bit words per field element
+    n32: usize,
+    /// Number of witness values produced by the circuit
+    witness_size: usize,
+}
+
+impl WitnessCalculator {
+    /// Instantiate a circom 2.x witness generator
+    pub fn new(wasm_bytes: &[u8]) -> Result<Self, String> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, wasm_bytes)
+            .map_err(|e| format!("Failed to compile witness WASM: {}", e))?;
+        let mut store = Store::new(&engine, HostState::default());
+        let mut linker = <Linker<HostState>>::new(&engine);
+
+        let link_err = |e| format!("Failed to link witness WASM: {}", e);
+        linker
+            .func_wrap(
+                "runtime",
+                "exceptionHandler",
+                |caller: Caller<'_, HostState>, code: i32| -> Result<(), Trap> {
+                    let mut message = match code {
+                        1 => "Signal not found".to_string(),
+                        2 => "Too many signals set".to_string(),
+                        3 => "Signal already set".to_string(),
+                        4 => "Assert Failed".to_string(),
+                        5 => "Not enough memory".to_string(),
+                        6 => "Input signal array access exceeds the size".to_string(),
+                        _ => format!("Unknown error {}", code),
+                    };
+                    if let Some(detail) = caller.data().messages.last() {
+                        message = format!("{}: {}", message, detail);
+                    }
+                    Err(Trap::new(message))
+                },
+            )
+            .map_err(link_err)?;
+        linker
+            .func_wrap("runtime", "printErrorMessage", |mut caller: Caller<'_, HostState>| {
+                let message = read_circuit_message(&mut caller);
+                caller.data_mut().messages.push(message);
+            })
+            .map_err(link_err)?;
+        linker
+            .func_wrap("runtime", "writeBufferMessage", |mut caller: Caller<'_, HostState>| {
+                let message = read_circuit_message(&mut caller);
+                caller.data_mut().messages.push(message);
+            })
+            .map_err(link_err)?;
+        linker
+            .func_wrap("runtime", "showSharedRWMemory", |_: Caller<'_, HostState>| {})
+            .map_err(link_err)?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Failed to instantiate witness WASM: {}", e))?;
+
+        let mut calculator = Self {
+            store,
+            instance,
+            n32: 0,
+            witness_size: 0,
+        };
+        calculator.n32 = calculator.call_get("getFieldNumLen32")? as usize;
+        calculator.call_void("getRawPrime")?;
+        let prime = calculator.read_words()?;
+        check_prime(&prime)?;
+        calculator.witness_size = calculator.call_get("getWitnessSize")? as usize;
+        Ok(calculator)
+    }
+
+    /// Number of witness values produced by the circuit
+    pub fn witness_size(&self) -> usize {
+        self.witness_size
+    }
+
+    /// Compute the full witness for a circom input object.
+    ///
+    /// `inputs` maps signal names to a value or (nested) array of values,
+    /// given as decimal strings, `0x` hex strings or JSON numbers. Every input
+    /// signal must be set; like snarkjs, missing ones are an error rather
+    /// than zeros.
+    pub fn calculate_witness(&mut self, inputs: &serde_json::Value) -> Result<Vec<Fr>, String> {
+        let inputs = inputs
+            .as_object()
+            .ok_or_else(|| "Circuit inputs must be a JSON object".to_string())?;
+
+        self.call_set("init", 0)?;
+
+        for (name, value) in inputs {
+            let hash = fnv_hash(name);
+            let (msb, lsb) = ((hash >> 32) as i32, hash as i32);
+
+            let mut values = Vec::new();
+            flatten_input(value, &mut values)
+                .map_err(|e| format!("Invalid value for input '{}': {}", name, e))?;
+
+            let size = self.call_input_size(msb, lsb)?;
+            if size < 0 {
+                return Err(format!("Input signal '{}' not found in circuit", name));
+            }
+            if values.len() != size as usize {
+                return Err(format!(
+                    "Input signal '{}' expects {} values, got {}",
+                    name,
+                    size,
+                    values.len()
+                ));
+            }
+
+            for (i, value) in values.iter().enumerate() {
+                self.write_field(value)?;
+                self.call_set_input(msb, lsb, i as i32)
+                    .map_err(|e| format!("Failed to set input '{}[{}]': {}", name, i, e))?;
+            }
+        }
+
+        let remaining = self.call_get("getRemainingInputsToBeSet")?;
+        if remaining != 0 {
+            let total = self.call_get("getInputSize")?;
+            return Err(format!(
+                "Not all inputs have been set: {} of {} input values are missing",
+                remaining, total
+            ));
+        }
+
+        (0..self.witness_size)
+            .map(|i| {
+                self.call_set("getWitness", i as i32)?;
+                self.read_field()
+            })
+            .collect()
+    }
+
+    fn call_get(&mut self, name: &str) -> Result<i32, String> {
+        self.instance
+            .get_typed_func::<(), i32>(&self.store, name)
+            .map_err(|e| format!("Witness WASM export '{}' missing: {}", name, e))?
+            .call(&mut self.store, ())
+            .map_err(|e| format!("Witness WASM '{}' failed: {}", name, e))
+    }
+
+    fn call_void(&mut self, name: &str) -> Result<(), String> {
+        self.instance
+            .get_typed_func::<(), ()>(&self.store, name)
+            .map_err(|e| format!("Witness WASM export '{}' missing: {}", name, e))?
+            .call(&mut self.store, ())
+            .map_err(|e| format!("Witness WASM '{}' failed: {}", name, e))
+    }
+
+    fn call_set(&mut self, name: &str, arg: i32) -> Result<(), String> {
+        self.instance
+            .get_typed_func::<i32, ()>(&self.store, name)
+            .map_err(|e| format!("Witness WASM export '{}' missing: {}", name, e))?
+            .call(&mut self.store, arg)
+            .map_err(|e| format!("Witness WASM '{}' failed: {}", name, e))
+    }
+
+    fn call_input_size(&mut self, msb: i32, lsb: i32) -> Result<i32, String> {
+        self.instance
+            .get_typed_func::<(i32, i32), i32>(&self.store, "getInputSignalSize")
+            .map_err(|e| format!("Witness WASM export 'getInputSignalSize' missing: {}", e))?
+            .call(&mut self.store, (msb, lsb))
+            .map_err(|e| e.to_string())
+    }
+
+    fn call_set_input(&mut self, msb: i32, lsb: i32, pos: i32) -> Result<(), String> {
+        self.instance
+            .get_typed_func::<(i32, i32, i32), ()>(&self.store, "setInputSignal")
+            .map_err(|e| format!("Witness WASM export 'setInputSignal' missing: {}", e))?
+            .call(&mut self.store, (msb, lsb, pos))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Write a field element into the shared RW memory (little-endian words)
+    fn write_field(&mut self, value: &BigUint) -> Result<(), String> {
+        let func = self
+            .instance
+            .get_typed_func::<(i32, i32), ()>(&self.store, "writeSharedRWMemory")
+            .map_err(|e| format!("Witness WASM export 'writeSharedRWMemory' missing: {}", e))?;
+        let words = value.to_u32_digits();
+        for j in 0..self.n32 {
+            let word = words.get(j).copied().unwrap_or(0);
+            func.call(&mut self.store, (j as i32, word as i32))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Read a field element from the shared RW memory (little-endian words)
+    fn read_field(&mut self) -> Result<Fr, String> {
+        Ok(Fr::from(self.read_words()?))
+    }
+
+    fn read_words(&mut self) -> Result<BigUint, String> {
+        let func = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "readSharedRWMemory")
+            .map_err(|e| format!("Witness WASM export 'readSharedRWMemory' missing: {}", e))?;
+        let words = (0..self.n32)
+            .map(|j| func.call(&mut self.store, j as i32).map(|w| w as u32))
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(BigUint::from_slice(&words))
+    }
+}
+
+/// Only circuits compiled for the BN254 scalar field can be proven
+fn check_prime(prime: &BigUint) -> Result<(), String> {
+    let expected: BigUint = Fr::MODULUS.into();
+    if *prime != expected {
+        return Err(format!(
+            "Circuit is compiled for prime {} but only BN254 ({}) is supported",
+            prime, expected
+        ));
+    }
+    Ok(())
+}
+
+/// Read a NUL-terminated message from the circuit via `getMessageChar`
+fn read_circuit_message(caller: &mut Caller<'_, HostState>) -> String {
+    let func = match caller
+        .get_export("getMessageChar")
+        .and_then(Extern::into_func)
+        .and_then(|f| f.typed::<(), i32>(&*caller).ok())
+    {
+        Some(func) => func,
+        None => return String::new(),
+    };
+    let mut message = String::new();
+    while let Ok(c) = func.call(&mut *caller, ()) {
+        if c == 0 {
+            break;
+        }
+        message.push(c as u8 as char);
+    }
+    message
+}
+
+/// 64-bit FNV-1a hash of an input signal name, as used by circom
+fn fnv_hash(name: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Flatten a (nested) JSON input value into field elements
+fn flatten_input(value: &serde_json::Value, out: &mut Vec<BigUint>) -> Result<(), String> {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                flatten_input(item, out)?;
+            }
+            Ok(())
+        }
+        serde_json::Value::Number(n) => {
+            let n = n
+                .as_u64()
+                .ok_or_else(|| format!("'{}' is not a non-negative integer", n))?;
+            out.push(BigUint::from(n));
+            Ok(())
+        }
+        serde_json::Value::String(s) => {
+            out.push(parse_field_str(s)?);
+            Ok(())
+        }
+        serde_json::Value::Bool(b) => {
+            out.push(BigUint::from(*b as u32));
+            Ok(())
+        }
+        _ => Err(format!("unsupported value {}", value)),
+    }
+}
+
+/// Parse a decimal or `0x`-prefixed hex string into an integer
+pub(crate) fn parse_field_str(s: &str) -> Result<BigUint, String> {
+    let s = s.trim();
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(s.as_bytes(), 10),
+    };
+    parsed.ok_or_else(|| format!("'{}' is not a valid integer", s))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Encode a `.r1cs` file for the given constraints over BN254
+    pub(crate) fn encode_r1cs(
+        num_wires: u32,
+        num_pub_out: u32,
+        num_pub_in: u32,
+        num_prv_in: u32,
+        constraints: &[[Vec<(u32, u64)>; 3]],
+    ) -> Vec<u8> {
+        let prime = BigUint::parse_bytes(
+            b"21888242871839275222246405745257275088548364400416034343698204186575808495617",
+            10,
+        )
+        .unwrap();
+        let field = |v: &BigUint| {
+            let mut bytes = v.to_bytes_le();
+            bytes.resize(32, 0);
+            bytes
+        };
+
+        let mut header = Vec::new();
+        header.extend_from_slice(&32u32.to_le_bytes());
+        header.extend_from_slice(&field(&prime));
+        for v in [num_wires, num_pub_out, num_pub_in, num_prv_in] {
+            header.extend_from_slice(&v.to_le_bytes());
+        }
+        header.extend_from_slice(&(num_wires as u64).to_le_bytes());
+        header.extend_from_slice(&(constraints.len() as u32).to_le_bytes());
+
+        let mut body = Vec::new();
+        for constraint in constraints {
+            for lc in constraint {
+                body.extend_from_slice(&(lc.len() as u32).to_le_bytes());
+                for (wire, coeff) in lc {
+                    body.extend_from_slice(&wire.to_le_bytes());
+                    body.extend_from_slice(&field(&BigUint::from(*coeff)));
+                }
+            }
+        }
+
+        let mut out = b"r1cs".to_vec();
+        out.extend_from_slice(&1u32.to_le_bytes());
+        out.extend_from_slice(&2u32.to_le_bytes());
+        for (ty, section) in [(1u32, header), (2u32, body)] {
+            out.extend_from_slice(&ty.to_le_bytes());
+            out.extend_from_slice(&(section.len() as u64).to_le_bytes());
+            out.extend_from_slice(&section);
+        }
+        out
+    }
+
+    /// `c <== a * b` with public output `c` and private inputs `a`, `b`
+    pub(crate) fn multiplier_r1cs() -> Vec<u8> {
+        encode_r1cs(4, 1, 0, 2, &[[vec![(2, 1)], vec![(3, 1)], vec![(1, 1)]]])
+    }
+
+    pub(crate) fn multiplier_wasm() -> Vec<u8> {
+        wat::parse_str(include_str!("../testdata/multiplier.wat")).unwrap()
+    }
+
+    #[test]
+    fn test_parse_r1cs() {
+        let r1cs = parse_r1cs(&multiplier_r1cs()).unwrap();
+        assert_eq!(r1cs.num_wires, 4);
+        assert_eq!(r1cs.num_instance(), 2);
+        assert_eq!(r1cs.num_aux(), 2);
+        assert_eq!(r1cs.constraints.len(), 1);
+        assert_eq!(r1cs.constraints[0].2, vec![(1, Fr::from(1u64))]);
+    }
+
+    #[test]
+    fn test_header_counts_are_bounded_by_file_size() {
+        // A section count of u32::MAX with no sections behind it
+        let mut data = b"r1cs".to_vec();
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_r1cs(&data).unwrap_err().contains("Unexpected end"));
+
+        // A section claiming u64::MAX bytes
+        data.truncate(8);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(parse_r1cs(&data).is_err());
+
+        // A constraint count of u32::MAX over one constraint
+        let mut r1cs = multiplier_r1cs();
+        // File and section headers, then n8, prime, four counts and labels
+        let offset = 12 + 12 + 4 + 32 + 4 * 4 + 8;
+        r1cs[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(parse_r1cs(&r1cs).unwrap_err().contains("Unexpected end"));
+    }
+
+    #[test]
+    fn test_check_witness_reports_constraint_index() {
+        let r1cs_bytes = encode_r1cs(
+            5,
+            1,
+            0,
+            2,
+            &[
+                [vec![(2, 1)], vec![(3, 1)], vec![(4, 1)]],
+                [vec![(4, 1)], vec![(0, 1)], vec![(1, 1)]],
+            ],
+        );
+        let r1cs = parse_r1cs(&r1cs_bytes).unwrap();
+        let witness = |values: [u64; 5]| values.iter().map(|v| Fr::from(*v)).collect::<Vec<_>>();
+
+        assert!(r1cs.check_witness(&witness([1, 12, 3, 4, 12])).is_ok());
+        let err = r1cs.check_witness(&witness([1, 13, 3, 4, 12])).unwrap_err();
+        assert!(err.contains("Constraint 1 "), "{}", err);
+        let err = r1cs.check_witness(&witness([1, 12, 3, 5, 12])).unwrap_err();
+        assert!(err.contains("Constraint 0 "), "{}", err);
+    }
+
+    #[test]
+    fn test_witness_calculator() {
+        let mut calculator = WitnessCalculator::new(&multiplier_wasm()).unwrap();
+        let inputs = serde_json::json!({ "a": "3", "b": 11 });
+        let witness = calculator.calculate_witness(&inputs).unwrap();
+        let expected: Vec<Fr> = [1u64, 33, 3, 11].iter().map(|v| Fr::from(*v)).collect();
+        assert_eq!(witness, expected);
+
+        let unknown = serde_json::json!({ "a": "3", "c": "1" });
+        assert!(calculator.calculate_witness(&unknown).is_err());
+
+        // An omitted input must not silently default to zero
+        let omitted = serde_json::json!({ "a": "3" });
+        let err = calculator.calculate_witness(&omitted).unwrap_err();
+        assert!(err.contains("1 of 2 input values are missing"), "{}", err);
+    }
+}
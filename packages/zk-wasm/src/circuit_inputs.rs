@@ -0,0 +1,734 @@
+// ============================================================================
+// Circuit input generation
+// ============================================================================
+
+use crate::canonicalization::{field_end, parse_message};
+use crate::dkim::{
+    select_signature, signed_header_data, verify_signature_at, DkimSignature, SignaturePolicy,
+};
+use crate::error::ZkWasmError;
+use crate::limbs::{rsa_key_size, LimbConfig};
+use crate::nullifier;
+use crate::rsa::RsaPublicKey;
+use crate::snarkjs::field_to_decimal;
+use crate::{compute_sha256_partial_state, sha256_pad, PartialHashResult, SHA256_BLOCK_SIZE};
+use ark_bn254::Fr;
+use base64::{engine::general_purpose, Engine as _};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Canonicalized signed header data of the selected signature, after
+/// checking that `key` verifies it
+fn verified_header(
+    email: &[u8],
+    key: &RsaPublicKey,
+    policy: &SignaturePolicy,
+) -> Result<(Vec<u8>, DkimSignature), ZkWasmError> {
+    let message = parse_message(email);
+    let (index, signature) = select_signature(&message, policy)?;
+    verify_signature_at(&message, index, &signature, key)?;
+    Ok((signed_header_data(&message, index, &signature), signature))
+}
+
+fn signature_value(signature: &DkimSignature) -> Result<BigUint, ZkWasmError> {
+    general_purpose::STANDARD
+        .decode(&signature.b)
+        .map(|bytes| BigUint::from_bytes_be(&bytes))
+        .map_err(|_| ZkWasmError::InvalidBase64 { field: "b" })
+}
+
+/// Inputs of the circom `BracuVerifier` / zk-email `EmailVerifier`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircomInputs {
+    /// SHA-256 padded header, zero-filled to `max_header_bytes`
+    pub in_padded: Vec<u8>,
+    pub pubkey: Vec<String>,
+    pub signature: Vec<String>,
+    /// Length of the padded header before zero-filling
+    pub in_len_padded_bytes: usize,
+}
+
+impl CircomInputs {
+    /// The input object as given to snarkjs, with every number as a decimal string
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "in_padded": self.in_padded.iter().map(u8::to_string).collect::<Vec<_>>(),
+            "pubkey": self.pubkey,
+            "signature": self.signature,
+            "in_len_padded_bytes": self.in_len_padded_bytes.to_string(),
+        })
+    }
+}
+
+/// Build the circom inputs for `email` signed under `key`.
+///
+/// The signed headers are canonicalized as for DKIM verification, SHA-256
+/// padded, then zero-filled to `max_header_bytes` (a multiple of 64). Limbs
+/// use the circom 121-bit layout for the key's size.
+pub fn circom_inputs(
+    email: &[u8],
+    key: &RsaPublicKey,
+    policy: &SignaturePolicy,
+    max_header_bytes: usize,
+) -> Result<CircomInputs, ZkWasmError> {
+    circom_inputs_with_header(email, key, policy, max_header_bytes).map(|(inputs, _)| inputs)
+}
+
+/// `circom_inputs` along with the canonicalized header they were made from
+fn circom_inputs_with_header(
+    email: &[u8],
+    key: &RsaPublicKey,
+    policy: &SignaturePolicy,
+    max_header_bytes: usize,
+) -> Result<(CircomInputs, Vec<u8>), ZkWasmError> {
+    if max_header_bytes == 0 || !max_header_bytes.is_multiple_of(SHA256_BLOCK_SIZE) {
+        return Err(ZkWasmError::InvalidInput(format!(
+            "max_header_bytes must be a positive multiple of 64, got {}",
+            max_header_bytes
+        )));
+    }
+    let (header, signature) = verified_header(email, key, policy)?;
+
+    let mut in_padded = sha256_pad(&header);
+    let in_len_padded_bytes = in_padded.len();
+    if in_len_padded_bytes > max_header_bytes {
+        return Err(ZkWasmError::HeaderTooLong {
+            length: header.len(),
+            padded_length: in_len_padded_bytes,
+            max: max_header_bytes,
+        });
+    }
+    in_padded.resize(max_header_bytes, 0);
+
+    let config = LimbConfig::circom(rsa_key_size(&key.modulus)?)?;
+    config.check_modulus(&key.modulus)?;
+    let inputs = CircomInputs {
+        in_padded,
+        pubkey: config.to_limbs(&key.modulus)?,
+        signature: config.to_limbs(&signature_value(&signature)?)?,
+        in_len_padded_bytes,
+    };
+    Ok((inputs, header))
+}
+
+/// Inputs of the circom `BracuReviewVerifier`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircomReviewInputs {
+    pub circom: CircomInputs,
+    /// Start of the From address in `in_padded`
+    pub from_address_index: usize,
+    /// Length of the From address (not a circuit input)
+    pub from_address_length: usize,
+    /// The student's private `secret` (decimal)
+    pub secret: String,
+    /// Public `faculty` and `semester` inputs as decimal field elements
+    pub faculty: String,
+    pub semester: String,
+    /// The `review_nullifier` the proof will output
+    pub review_nullifier: String,
+}
+
+impl CircomReviewInputs {
+    /// The input object as given to snarkjs, with every number as a decimal string
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut inputs = self.circom.to_json();
+        inputs["from_address_index"] = json!(self.from_address_index.to_string());
+        inputs["secret"] = json!(self.secret);
+        inputs["faculty"] = json!(self.faculty);
+        inputs["semester"] = json!(self.semester);
+        inputs
+    }
+}
+
+/// Build the circom `BracuReviewVerifier` inputs: `circom_inputs` plus the
+/// From address location, the student's secret and the review's faculty and
+/// semester.
+pub fn circom_review_inputs(
+    email: &[u8],
+    key: &RsaPublicKey,
+    policy: &SignaturePolicy,
+    max_header_bytes: usize,
+    secret: Fr,
+    faculty_initials: &str,
+    semester: &str,
+) -> Result<CircomReviewInputs, ZkWasmError> {
+    let (circom, header) = circom_inputs_with_header(email, key, policy, max_header_bytes)?;
+    let (_, from_address) = from_sequences(&header)?;
+    let address = &header[from_address.index..from_address.end()];
+    let (faculty, semester_field) =
+        nullifier::review_scope(faculty_initials, semester).map_err(ZkWasmError::InvalidInput)?;
+    let review_nullifier = nullifier::address_nullifier(address, secret)
+        .and_then(|email_nullifier| {
+            nullifier::review_nullifier(email_nullifier, faculty_initials, semester)
+        })
+        .map_err(ZkWasmError::InvalidInput)?;
+    Ok(CircomReviewInputs {
+        circom,
+        from_address_index: from_address.index,
+        from_address_length: from_address.length,
+        secret: field_to_decimal(&secret),
+        faculty: field_to_decimal(&faculty),
+        semester: field_to_decimal(&semester_field),
+        review_nullifier: field_to_decimal(&review_nullifier),
+    })
+}
+
+/// Index and length of a span of the header, as zkemail.nr's `Sequence`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sequence {
+    pub index: usize,
+    pub length: usize,
+}
+
+impl Sequence {
+    fn end(&self) -> usize {
+        self.index + self.length
+    }
+
+    fn to_json(self) -> Value {
+        json!({ "index": self.index.to_string(), "length": self.length.to_string() })
+    }
+}
+
+/// The From field (without its CRLF) of canonicalized header data and the
+/// address inside it. A field runs to the first CRLF not followed by WSP, so
+/// a From folded under `c=simple` is taken whole.
+pub fn from_sequences(header: &[u8]) -> Result<(Sequence, Sequence), ZkWasmError> {
+    let is_space = |b: &u8| b" \t\r\n".contains(b);
+    let mut start = 0;
+    while start < header.len() {
+        let end = field_end(header, start);
+        let field = &header[start..end];
+        let colon = field.iter().position(|b| *b == b':');
+        let is_from = colon.is_some_and(|colon| {
+            let name = &field[..colon];
+            let name_end = name.iter().rposition(|b| !is_space(b)).map_or(0, |e| e + 1);
+            name[..name_end].eq_ignore_ascii_case(b"from")
+        });
+        if let (true, Some(colon)) = (is_from, colon) {
+            let from_header = Sequence {
+                index: start,
+                length: field.len(),
+            };
+            let value = &field[colon + 1..];
+            let (offset, address) = match (
+                value.iter().rposition(|b| *b == b'<'),
+                value.iter().rposition(|b| *b == b'>'),
+            ) {
+                (Some(open), Some(close)) if open < close => (open + 1, &value[open + 1..close]),
+                _ => {
+                    let begin = value.iter().position(|b| !is_space(b)).unwrap_or(0);
+                    let end = value.iter().rposition(|b| !is_space(b)).map_or(0, |e| e + 1);
+                    (begin, &value[begin..end.max(begin)])
+                }
+            };
+            if !address.contains(&b'@') {
+                return Err(ZkWasmError::NoFromAddress);
+            }
+            let from_address = Sequence {
+                index: start + colon + 1 + offset,
+                length: address.len(),
+            };
+            return Ok((from_header, from_address));
+        }
+        start = end + 2;
+    }
+    Err(ZkWasmError::NoFromHeader)
+}
+
+/// Inputs of the Noir `bracu_verifier` (partial header hash variant)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoirInputs {
+    partial_header_hash: Vec<u32>,
+    /// Header bytes after the precomputed blocks, zero-filled to the circuit size
+    remaining_header: Vec<u8>,
+    remaining_header_len: usize,
+    total_header_length: usize,
+    pubkey_modulus: Vec<String>,
+    pubkey_redc: Vec<String>,
+    signature: Vec<String>,
+    from_header_sequence: Sequence,
+    from_address_sequence: Sequence,
+}
+
+impl NoirInputs {
+    /// Input map in the layout of the circuit ABI, numbers as decimal strings
+    pub fn to_json_value(&self) -> Value {
+        let strings = |values: &[u8]| values.iter().map(u8::to_string).collect::<Vec<_>>();
+        json!({
+            "partial_header_hash": self.partial_header_hash.iter().map(u32::to_string).collect::<Vec<_>>(),
+            "remaining_header": {
+                "storage": strings(&self.remaining_header),
+                "len": self.remaining_header_len.to_string(),
+            },
+            "total_header_length": self.total_header_length.to_string(),
+            "pubkey": {
+                "modulus": self.pubkey_modulus,
+                "redc": self.pubkey_redc,
+            },
+            "signature": self.signature,
+            "from_header_sequence": self.from_header_sequence.to_json(),
+            "from_address_sequence": self.from_address_sequence.to_json(),
+        })
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl NoirInputs {
+    /// JSON input map for noir_js `execute`
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// The same inputs as a `Prover.toml` for nargo
+    pub fn to_prover_toml(&self) -> String {
+        let value = |v: &Value| match v {
+            Value::Array(items) => format!(
+                "[{}]",
+                items
+                    .iter()
+                    .map(|item| format!("'{}'", item.as_str().unwrap_or_default()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            other => format!("'{}'", other.as_str().unwrap_or_default()),
+        };
+        let mut lines = Vec::new();
+        let mut tables = Vec::new();
+        if let Value::Object(map) = self.to_json_value() {
+            for (key, v) in &map {
+                match v {
+                    Value::Object(fields) => {
+                        let body: String = fields
+                            .iter()
+                            .map(|(field, v)| format!("{} = {}\n", field, value(v)))
+                            .collect();
+                        tables.push(format!("[{}]\n{}", key, body));
+                    }
+                    other => lines.push(format!("{} = {}", key, value(other))),
+                }
+            }
+        }
+        lines.extend(tables);
+        lines.join("\n")
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn remaining_header_len(&self) -> usize {
+        self.remaining_header_len
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn total_header_length(&self) -> usize {
+        self.total_header_length
+    }
+}
+
+/// Build the Noir inputs for `email` signed under `key`.
+///
+/// The canonicalized signed header is split with `precompute_header` so that
+/// at most `max_remaining_len` bytes (the circuit's `BoundedVec` size)
+/// remain, and the From sequences are given relative to those bytes.
+pub fn noir_inputs(
+    email: &[u8],
+    key: &RsaPublicKey,
+    policy: &SignaturePolicy,
+    max_remaining_len: usize,
+) -> Result<NoirInputs, ZkWasmError> {
+    let (header, signature) = verified_header(email, key, policy)?;
+    let precomputed = precompute_header(&header, max_remaining_len)?;
+    let partial = precomputed.partial;
+
+    let remaining_header_len = partial.remaining.len();
+    let mut remaining_header = partial.remaining;
+    remaining_header.resize(max_remaining_len, 0);
+
+    let config = LimbConfig::noir(rsa_key_size(&key.modulus)?)?;
+    config.check_modulus(&key.modulus)?;
+    Ok(NoirInputs {
+        partial_header_hash: partial.state,
+        remaining_header,
+        remaining_header_len,
+        total_header_length: header.len(),
+        pubkey_modulus: config.to_limbs(&key.modulus)?,
+        pubkey_redc: config.redc_param(&key.modulus)?,
+        signature: config.to_limbs(&signature_value(&signature)?)?,
+        from_header_sequence: precomputed.from_header,
+        from_address_sequence: precomputed.from_address,
+    })
+}
+
+/// Partial hash of the signed header together with the From sequences,
+/// indexed into `remaining` (the bytes the circuit sees)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct PrecomputedHeader {
+    partial: PartialHashResult,
+    from_header: Sequence,
+    from_address: Sequence,
+    from_email: String,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl PrecomputedHeader {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn partial_hash(&self) -> PartialHashResult {
+        self.partial.clone()
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn from_header_index(&self) -> usize {
+        self.from_header.index
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn from_header_length(&self) -> usize {
+        self.from_header.length
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn from_address_index(&self) -> usize {
+        self.from_address.index
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn from_address_length(&self) -> usize {
+        self.from_address.length
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn from_email(&self) -> String {
+        self.from_email.clone()
+    }
+}
+
+/// Precompute the SHA-256 blocks of `header` that come before its From field.
+///
+/// Headers of at most `max_remaining_len` bytes are left whole. Otherwise the
+/// split is the last 64-byte boundary at or before the start of From, so the
+/// From field always lies entirely in `remaining`; if that leaves more than
+/// `max_remaining_len` bytes, From starts too early and this fails.
+pub fn precompute_header(
+    header: &[u8],
+    max_remaining_len: usize,
+) -> Result<PrecomputedHeader, ZkWasmError> {
+    let (from_header, from_address) = from_sequences(header)?;
+
+    let split = if header.len() <= max_remaining_len {
+        0
+    } else {
+        let split = from_header.index / SHA256_BLOCK_SIZE * SHA256_BLOCK_SIZE;
+        if header.len() - split > max_remaining_len {
+            return Err(ZkWasmError::FromHeaderTooEarly {
+                from_index: from_header.index,
+                header_length: header.len(),
+                max_remaining: max_remaining_len,
+            });
+        }
+        split
+    };
+
+    let rebase = |sequence: Sequence| Sequence {
+        index: sequence.index - split,
+        length: sequence.length,
+    };
+    let precomputed = PrecomputedHeader {
+        partial: PartialHashResult {
+            state: compute_sha256_partial_state(&header[..split]),
+            remaining: header[split..].to_vec(),
+            total_length: header.len() as u64,
+            prehashed_length: split as u64,
+        },
+        from_header: rebase(from_header),
+        from_address: rebase(from_address),
+        from_email: String::from_utf8_lossy(&header[from_address.index..from_address.end()])
+            .to_ascii_lowercase(),
+    };
+    precomputed.check_bounds()?;
+    Ok(precomputed)
+}
+
+impl PrecomputedHeader {
+    /// The checks the circuit makes on the sequences, so that a bad split
+    /// fails here rather than as an opaque constraint failure
+    fn check_bounds(&self) -> Result<(), ZkWasmError> {
+        let remaining_len = self.partial.remaining.len();
+        for (sequence, name) in [
+            (self.from_header, "From header"),
+            (self.from_address, "From address"),
+        ] {
+            if sequence.end() > remaining_len {
+                return Err(ZkWasmError::InvalidInput(format!(
+                    "The {} ends at byte {}, past the {} remaining header bytes",
+                    name,
+                    sequence.end(),
+                    remaining_len
+                )));
+            }
+        }
+        if self.from_address.index < self.from_header.index
+            || self.from_address.end() > self.from_header.end()
+        {
+            return Err(ZkWasmError::InvalidInput(
+                "The From address is not inside the From header".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkim::tests::{sign_email, TEST_EMAIL};
+    use crate::rsa::tests::test_key;
+
+    #[test]
+    fn test_circom_inputs() {
+        let (key, _) = test_key();
+        let email = sign_email(TEST_EMAIL);
+        let policy = SignaturePolicy::FromAligned;
+        let inputs = circom_inputs(email.as_bytes(), &key, &policy, 1024).unwrap();
+
+        let (header, _) = verified_header(email.as_bytes(), &key, &policy).unwrap();
+        let padded = sha256_pad(&header);
+        assert_eq!(inputs.in_len_padded_bytes, padded.len());
+        assert_eq!(&inputs.in_padded[..padded.len()], padded.as_slice());
+        assert!(inputs.in_padded[padded.len()..].iter().all(|b| *b == 0));
+        assert_eq!(inputs.in_padded.len(), 1024);
+        assert_eq!(
+            inputs.pubkey,
+            LimbConfig::CIRCOM_2048.to_limbs(&key.modulus).unwrap()
+        );
+        assert_eq!(inputs.signature.len(), 17);
+
+        let json = inputs.to_json();
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(
+            keys,
+            ["in_len_padded_bytes", "in_padded", "pubkey", "signature"]
+        );
+        assert_eq!(json["in_len_padded_bytes"], padded.len().to_string());
+        assert_eq!(json["in_padded"][0], (b'f' as u32).to_string());
+    }
+
+    #[test]
+    fn test_circom_inputs_errors() {
+        let (key, _) = test_key();
+        let email = sign_email(TEST_EMAIL);
+        let policy = SignaturePolicy::FromAligned;
+
+        let err = circom_inputs(email.as_bytes(), &key, &policy, 128).unwrap_err();
+        assert!(matches!(err, ZkWasmError::HeaderTooLong { max: 128, .. }), "{}", err);
+        assert!(err.to_string().contains("more than the circuit's 128"), "{}", err);
+        let err = circom_inputs(email.as_bytes(), &key, &policy, 1000).unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+
+        let wrong_key = RsaPublicKey::from_modulus(&key.modulus - 2u32);
+        let err = circom_inputs(email.as_bytes(), &wrong_key, &policy, 1024).unwrap_err();
+        assert_eq!(err.code(), "DKIM_VERIFICATION_FAILED", "{}", err);
+    }
+
+    /// TEST_EMAIL with a signed `X-Pad` header of `pad` bytes placed before
+    /// (or after) From in the signed header data
+    fn padded_email(pad: usize, before_from: bool) -> String {
+        let h = if before_from {
+            "h=x-pad:from:to:subject"
+        } else {
+            "h=from:to:subject:x-pad"
+        };
+        let email = TEST_EMAIL.replace("h=from:to:subject", h).replacen(
+            "From:",
+            &format!("X-Pad: {}\r\nFrom:", "x".repeat(pad)),
+            1,
+        );
+        sign_email(&email)
+    }
+
+    #[test]
+    fn test_from_sequences() {
+        let header = b"to:bob@example.org\r\nfrom:\"Alice <x>\" <alice@example.com>\r\nsubject:hi";
+        let (from_header, from_address) = from_sequences(header).unwrap();
+        assert_eq!(
+            &header[from_header.index..from_header.end()],
+            b"from:\"Alice <x>\" <alice@example.com>".as_slice()
+        );
+        assert_eq!(
+            &header[from_address.index..from_address.end()],
+            b"alice@example.com".as_slice()
+        );
+
+        let (_, bare) = from_sequences(b"from: bob@example.org \r\n").unwrap();
+        assert_eq!(
+            bare,
+            Sequence {
+                index: 6,
+                length: 15
+            }
+        );
+        assert_eq!(
+            from_sequences(b"from:undisclosed\r\n"),
+            Err(ZkWasmError::NoFromAddress)
+        );
+        assert_eq!(
+            from_sequences(b"to:bob@example.org\r\n"),
+            Err(ZkWasmError::NoFromHeader)
+        );
+    }
+
+    /// Under `c=simple` the From field keeps its folds and the spacing of its name
+    #[test]
+    fn test_from_sequences_simple_folded() {
+        let header = b"To: bob@example.org\r\nFrom: \"Alice Example\"\r\n\t<alice@example.com>\r\n\
+            Subject: hi\r\nDKIM-Signature: v=1; b=";
+        let (from_header, from_address) = from_sequences(header).unwrap();
+        assert_eq!(
+            &header[from_header.index..from_header.end()],
+            b"From: \"Alice Example\"\r\n\t<alice@example.com>".as_slice()
+        );
+        assert_eq!(
+            &header[from_address.index..from_address.end()],
+            b"alice@example.com".as_slice()
+        );
+
+        let header = b"To: bob@example.org\r\nFROM :\r\n alice@example.com\r\nSubject: hi\r\n";
+        let (from_header, from_address) = from_sequences(header).unwrap();
+        assert_eq!(from_header, Sequence { index: 21, length: 26 });
+        assert_eq!(
+            &header[from_address.index..from_address.end()],
+            b"alice@example.com".as_slice()
+        );
+
+        // The signed header of a c=simple email with a folded From
+        let email = sign_email(
+            &TEST_EMAIL
+                .replace("c=relaxed/relaxed", "c=simple/simple")
+                .replace("From: Alice <alice@example.com>", "From: Alice\r\n <alice@example.com>"),
+        );
+        let (key, _) = test_key();
+        let (header, _) =
+            verified_header(email.as_bytes(), &key, &SignaturePolicy::FromAligned).unwrap();
+        let (from_header, from_address) = from_sequences(&header).unwrap();
+        assert_eq!(
+            &header[from_header.index..from_header.end()],
+            b"From: Alice\r\n <alice@example.com>".as_slice()
+        );
+        assert_eq!(
+            &header[from_address.index..from_address.end()],
+            b"alice@example.com".as_slice()
+        );
+
+        // A folded Subject mentioning From: is not the From field
+        let header = b"Subject: re\r\n from: x@y.z\r\nfrom: a@b.c\r\n";
+        let (_, from_address) = from_sequences(header).unwrap();
+        assert_eq!(&header[from_address.index..from_address.end()], b"a@b.c".as_slice());
+    }
+
+    #[test]
+    fn test_noir_inputs() {
+        let (key, _) = test_key();
+        let email = sign_email(TEST_EMAIL);
+        let inputs =
+            noir_inputs(email.as_bytes(), &key, &SignaturePolicy::FromAligned, 2048).unwrap();
+        let remaining = &inputs.remaining_header[..inputs.remaining_header_len];
+        let span = |s: Sequence| &remaining[s.index..s.end()];
+        assert_eq!(
+            span(inputs.from_header_sequence),
+            b"from:Alice <alice@example.com>"
+        );
+        assert_eq!(span(inputs.from_address_sequence), b"alice@example.com");
+        assert_eq!(inputs.remaining_header.len(), 2048);
+        assert_eq!(inputs.total_header_length, inputs.remaining_header_len);
+        assert_eq!(
+            inputs.pubkey_redc,
+            LimbConfig::NOIR_2048.redc_param(&key.modulus).unwrap()
+        );
+
+        let json = inputs.to_json_value();
+        assert_eq!(
+            json["remaining_header"]["len"],
+            inputs.remaining_header_len.to_string()
+        );
+        assert_eq!(json["from_address_sequence"]["length"], "17");
+        assert_eq!(json["signature"].as_array().unwrap().len(), 18);
+        let toml = inputs.to_prover_toml();
+        assert!(toml.starts_with("partial_header_hash = ['"), "{}", toml);
+        assert!(toml.contains("\n[remaining_header]\nlen = '"), "{}", toml);
+        assert!(toml.contains("[pubkey]\nmodulus = ['"));
+    }
+
+    #[test]
+    fn test_noir_inputs_with_precomputed_prefix() {
+        let (key, _) = test_key();
+        let email = padded_email(400, true);
+        let policy = SignaturePolicy::FromAligned;
+        let (header, _) = verified_header(email.as_bytes(), &key, &policy).unwrap();
+
+        let inputs = noir_inputs(email.as_bytes(), &key, &policy, header.len() - 100).unwrap();
+        let prehashed = header.len() - inputs.remaining_header_len;
+        assert!(prehashed > 0 && prehashed.is_multiple_of(64));
+        assert_eq!(
+            &inputs.remaining_header[..inputs.remaining_header_len],
+            &header[prehashed..]
+        );
+        let address = inputs.from_address_sequence;
+        assert_eq!(
+            &inputs.remaining_header[address.index..address.end()],
+            b"alice@example.com"
+        );
+
+        // From comes first in the signed data, so nothing can be precomputed
+        let email = padded_email(400, false);
+        let err = noir_inputs(email.as_bytes(), &key, &policy, 256).unwrap_err();
+        assert_eq!(err.code(), "FROM_HEADER_TOO_EARLY", "{}", err);
+    }
+
+    #[test]
+    fn test_precompute_header_keeps_from_in_the_buffer() {
+        let header = format!(
+            "x-pad:{}\r\nfrom:Alice <alice@example.com>\r\nto:bob@example.org\r\ndkim-signature:v=1; b=",
+            "x".repeat(300)
+        );
+        let header = header.as_bytes();
+        let from = header.windows(5).position(|w| w == b"from:").unwrap();
+
+        for max in [header.len(), 200, header.len() - from / 64 * 64] {
+            let precomputed = precompute_header(header, max).unwrap();
+            let partial = &precomputed.partial;
+            let split = partial.prehashed_length as usize;
+            assert!(split <= from && split.is_multiple_of(64));
+            assert!(partial.remaining.len() <= max);
+            assert_eq!(partial.remaining, &header[split..]);
+            assert_eq!(
+                partial.state,
+                compute_sha256_partial_state(&header[..split])
+            );
+
+            let span = |s: Sequence| &partial.remaining[s.index..s.end()];
+            assert!(span(precomputed.from_header).starts_with(b"from:"));
+            assert_eq!(span(precomputed.from_address), b"alice@example.com");
+            assert_eq!(precomputed.from_email, "alice@example.com");
+        }
+        assert_eq!(
+            precompute_header(header, header.len())
+                .unwrap()
+                .partial
+                .prehashed_length,
+            0
+        );
+
+        // Splitting at or before From leaves more than 100 bytes
+        let err = precompute_header(header, 100).unwrap_err();
+        assert_eq!(
+            err,
+            ZkWasmError::FromHeaderTooEarly {
+                from_index: from,
+                header_length: header.len(),
+                max_remaining: 100,
+            }
+        );
+    }
+}
@@ -0,0 +1,725 @@
+// ============================================================================
+// DKIM-Signature tag-list parsing (RFC 6376 §3.2, §3.5)
+// ============================================================================
+
+use crate::canonicalization::{self, Canonicalization, ParsedMessage};
+use crate::dkim_key::{DkimKeyRecord, KeyRecordError};
+use crate::rsa::{verify_pkcs1v15_sha256, RsaError, RsaPublicKey};
+use crate::sha256;
+use base64::{engine::general_purpose, Engine as _};
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Error produced while parsing a DKIM tag-list
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DkimTagError {
+    /// A tag-spec that is not `name=value`, or has an invalid tag name
+    Malformed(String),
+    /// The same tag appears more than once
+    DuplicateTag(String),
+    /// A required tag is absent
+    MissingTag(&'static str),
+    /// A tag is present but its value is not acceptable
+    InvalidValue { tag: &'static str, reason: String },
+}
+
+impl fmt::Display for DkimTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(spec) => write!(f, "Malformed DKIM tag '{}'", spec),
+            Self::DuplicateTag(tag) => write!(f, "Duplicate DKIM tag '{}='", tag),
+            Self::MissingTag(tag) => write!(f, "Missing required DKIM tag '{}='", tag),
+            Self::InvalidValue { tag, reason } => {
+                write!(f, "Invalid DKIM tag '{}=': {}", tag, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DkimTagError {}
+
+/// Split a tag-list into `(name, value)` pairs in their original order.
+///
+/// Surrounding folding whitespace is trimmed from names and values, an empty
+/// trailing tag-spec is allowed and duplicate names are rejected.
+pub fn parse_tag_list(list: &str) -> Result<Vec<(String, String)>, DkimTagError> {
+    let mut tags: Vec<(String, String)> = Vec::new();
+    let specs: Vec<&str> = list.split(';').collect();
+
+    for (i, spec) in specs.iter().enumerate() {
+        if spec.trim().is_empty() {
+            if i + 1 == specs.len() {
+                break;
+            }
+            return Err(DkimTagError::Malformed(spec.trim().to_string()));
+        }
+        let (name, value) = spec
+            .split_once('=')
+            .ok_or_else(|| DkimTagError::Malformed(spec.trim().to_string()))?;
+        let name = name.trim_matches(is_fws);
+        if !is_tag_name(name) {
+            return Err(DkimTagError::Malformed(spec.trim().to_string()));
+        }
+        if tags.iter().any(|(existing, _)| existing == name) {
+            return Err(DkimTagError::DuplicateTag(name.to_string()));
+        }
+        tags.push((name.to_string(), value.trim_matches(is_fws).to_string()));
+    }
+    Ok(tags)
+}
+
+fn is_fws(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+/// `tag-name = ALPHA *ALNUMPUNC`
+fn is_tag_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Remove all folding whitespace, as for base64 tag values
+pub(crate) fn strip_fws(value: &str) -> String {
+    value.chars().filter(|c| !is_fws(*c)).collect()
+}
+
+/// Every tag of a DKIM-Signature header field
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DkimSignature {
+    /// Version (`v=`), always "1"
+    pub v: String,
+    /// Signing algorithm (`a=`), e.g. "rsa-sha256"
+    pub a: String,
+    /// Canonicalization (`c=`) as written; "simple/simple" when absent
+    pub c: String,
+    /// Signing domain (`d=`)
+    pub d: String,
+    /// Selector (`s=`)
+    pub s: String,
+    /// Signed header field names (`h=`) in signing order
+    pub h: Vec<String>,
+    /// Body hash (`bh=`), base64 with whitespace removed
+    pub bh: String,
+    /// Signature (`b=`), base64 with whitespace removed
+    pub b: String,
+    /// Body length limit (`l=`)
+    pub l: Option<u64>,
+    /// Signature timestamp (`t=`)
+    pub t: Option<u64>,
+    /// Signature expiration (`x=`)
+    pub x: Option<u64>,
+    /// Agent or user identifier (`i=`)
+    pub i: Option<String>,
+    /// Query methods (`q=`)
+    pub q: Option<String>,
+}
+
+impl DkimSignature {
+    /// Parse the value of a DKIM-Signature header field (everything after the
+    /// colon). Unknown tags are ignored, as RFC 6376 requires.
+    pub fn parse(value: &str) -> Result<Self, DkimTagError> {
+        let tags = parse_tag_list(value)?;
+        let get = |name: &str| {
+            tags.iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.clone())
+        };
+        let required = |name: &'static str| get(name).ok_or(DkimTagError::MissingTag(name));
+        let number = |name: &'static str| {
+            get(name)
+                .map(|value| {
+                    value.parse::<u64>().map_err(|_| DkimTagError::InvalidValue {
+                        tag: name,
+                        reason: format!("'{}' is not an unsigned integer", value),
+                    })
+                })
+                .transpose()
+        };
+
+        let v = required("v")?;
+        if v != "1" {
+            return Err(DkimTagError::InvalidValue {
+                tag: "v",
+                reason: format!("unsupported version '{}'", v),
+            });
+        }
+
+        let a = required("a")?;
+        if a.split_once('-').is_none_or(|(k, h)| k.is_empty() || h.is_empty()) {
+            return Err(DkimTagError::InvalidValue {
+                tag: "a",
+                reason: format!("'{}' is not of the form <key>-<hash>", a),
+            });
+        }
+
+        let c = get("c").unwrap_or_else(|| "simple/simple".to_string());
+        Canonicalization::parse_tag(Some(&c))
+            .map_err(|reason| DkimTagError::InvalidValue { tag: "c", reason })?;
+
+        let d = required("d")?;
+        let s = required("s")?;
+        if d.is_empty() || s.is_empty() {
+            let tag = if d.is_empty() { "d" } else { "s" };
+            return Err(DkimTagError::InvalidValue {
+                tag,
+                reason: "value is empty".to_string(),
+            });
+        }
+
+        let h: Vec<String> = required("h")?
+            .split(':')
+            .map(|name| name.trim_matches(is_fws).to_string())
+            .collect();
+        if h.iter().any(String::is_empty) {
+            return Err(DkimTagError::InvalidValue {
+                tag: "h",
+                reason: "empty header field name".to_string(),
+            });
+        }
+        if !h.iter().any(|name| name.eq_ignore_ascii_case("from")) {
+            return Err(DkimTagError::InvalidValue {
+                tag: "h",
+                reason: "the From header field must be signed".to_string(),
+            });
+        }
+
+        let bh = strip_fws(&required("bh")?);
+        let b = strip_fws(&required("b")?);
+
+        let l = number("l")?;
+        let t = number("t")?;
+        let x = number("x")?;
+        if let (Some(t), Some(x)) = (t, x) {
+            if x < t {
+                return Err(DkimTagError::InvalidValue {
+                    tag: "x",
+                    reason: format!("expiration {} is before the timestamp {}", x, t),
+                });
+            }
+        }
+
+        let i = get("i");
+        if let Some(identity) = &i {
+            let domain = identity.rsplit_once('@').map(|(_, domain)| domain).unwrap_or("");
+            if !is_same_or_subdomain(domain, &d) {
+                return Err(DkimTagError::InvalidValue {
+                    tag: "i",
+                    reason: format!("'{}' is not within the signing domain '{}'", identity, d),
+                });
+            }
+        }
+
+        Ok(Self {
+            v,
+            a,
+            c,
+            d,
+            s,
+            h,
+            bh,
+            b,
+            l,
+            t,
+            x,
+            i,
+            q: get("q"),
+        })
+    }
+
+    /// Header and body canonicalization algorithms from `c=`
+    pub fn canonicalization(&self) -> (Canonicalization, Canonicalization) {
+        // Already validated by `parse`
+        Canonicalization::parse_tag(Some(&self.c))
+            .unwrap_or((Canonicalization::Simple, Canonicalization::Simple))
+    }
+}
+
+/// Which DKIM-Signature to use when a message carries several
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignaturePolicy {
+    /// The signature whose `d=` equals this domain
+    Domain(String),
+    /// The signature whose `d=` is exactly the From address domain.
+    ///
+    /// Parent domains are not accepted: without a public suffix list a parent
+    /// such as `ac.bd` cannot be told apart from a registrable domain.
+    FromAligned,
+}
+
+/// Every DKIM-Signature field in header order, with its index into
+/// `message.headers` and the result of parsing it
+pub fn signature_fields(
+    message: &ParsedMessage,
+) -> Vec<(usize, Result<DkimSignature, DkimTagError>)> {
+    message
+        .headers
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.name.eq_ignore_ascii_case("DKIM-Signature"))
+        .map(|(index, field)| (index, DkimSignature::parse(&field.value())))
+        .collect()
+}
+
+/// Why no DKIM-Signature could be selected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// The message has no DKIM-Signature field
+    NotFound,
+    /// Every DKIM-Signature failed to parse; holds the first error
+    Invalid(DkimTagError),
+    /// `FromAligned` was asked for but the From address has no domain
+    NoFromDomain,
+    /// The message has more than one From field (RFC 6376 §8.15)
+    MultipleFrom(usize),
+    /// No signature satisfies the policy
+    NoMatch { wanted: String, available: Vec<String> },
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "No DKIM-Signature header found"),
+            Self::Invalid(e) => write!(f, "No valid DKIM-Signature header found: {}", e),
+            Self::NoFromDomain => write!(
+                f,
+                "Could not find a From address to align the DKIM signature with"
+            ),
+            Self::MultipleFrom(count) => write!(
+                f,
+                "The message has {} From fields; only one is allowed",
+                count
+            ),
+            Self::NoMatch { wanted, available } => write!(
+                f,
+                "No DKIM-Signature matches {} (signatures found for d={})",
+                wanted,
+                available.join(", d=")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Pick the signature that satisfies `policy`; the topmost one wins a tie.
+///
+/// Signatures that fail to parse are never selected. Messages with several
+/// From fields are rejected: an unsigned From prepended above the signed one
+/// would otherwise be shown to the user or steer the selection.
+pub fn select_signature(
+    message: &ParsedMessage,
+    policy: &SignaturePolicy,
+) -> Result<(usize, DkimSignature), SignatureError> {
+    let fields = signature_fields(message);
+    if fields.is_empty() {
+        return Err(SignatureError::NotFound);
+    }
+    let from_count = message
+        .headers
+        .iter()
+        .filter(|field| field.name.eq_ignore_ascii_case("From"))
+        .count();
+    if from_count > 1 {
+        return Err(SignatureError::MultipleFrom(from_count));
+    }
+    let valid: Vec<(usize, &DkimSignature)> = fields
+        .iter()
+        .filter_map(|(index, parsed)| parsed.as_ref().ok().map(|sig| (*index, sig)))
+        .collect();
+    if valid.is_empty() {
+        let first_error = fields.iter().find_map(|(_, parsed)| parsed.as_ref().err());
+        return Err(SignatureError::Invalid(
+            first_error.cloned().expect("every field failed to parse"),
+        ));
+    }
+
+    let selected = match policy {
+        SignaturePolicy::Domain(domain) => {
+            let domain = domain.trim_end_matches('.');
+            valid
+                .iter()
+                .find(|(_, sig)| sig.d.trim_end_matches('.').eq_ignore_ascii_case(domain))
+        }
+        SignaturePolicy::FromAligned => {
+            let from_domain = from_domain(message).ok_or(SignatureError::NoFromDomain)?;
+            valid
+                .iter()
+                .find(|(_, sig)| sig.d.trim_end_matches('.').eq_ignore_ascii_case(&from_domain))
+        }
+    };
+
+    selected
+        .map(|(index, sig)| (*index, (*sig).clone()))
+        .ok_or_else(|| SignatureError::NoMatch {
+            wanted: match policy {
+                SignaturePolicy::Domain(domain) => format!("d={}", domain),
+                SignaturePolicy::FromAligned => format!(
+                    "the From domain '{}'",
+                    from_domain(message).unwrap_or_default()
+                ),
+            },
+            available: valid.iter().map(|(_, sig)| sig.d.clone()).collect(),
+        })
+}
+
+/// Domain of the From address, lowercased.
+///
+/// Reads the bottom-most From field, the instance a signature over `h=from`
+/// covers (see [`canonicalization::select_signed_headers`]).
+pub fn from_domain(message: &ParsedMessage) -> Option<String> {
+    let field = message
+        .headers
+        .iter()
+        .rev()
+        .find(|field| field.name.eq_ignore_ascii_case("From"))?;
+    let value = field.value();
+    let address = match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value.trim_matches(is_fws),
+    };
+    let (_, domain) = address.rsplit_once('@')?;
+    let domain = domain.trim_matches(is_fws).to_ascii_lowercase();
+    (!domain.is_empty()).then_some(domain)
+}
+
+/// The step at which DKIM verification failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DkimVerifyError {
+    /// No usable DKIM-Signature (missing, malformed or not matching the policy)
+    Signature(SignatureError),
+    /// `a=` names an algorithm other than rsa-sha256
+    UnsupportedAlgorithm(String),
+    /// `x=` is before the verification time
+    Expired { expiration: u64, now: u64 },
+    /// The key record's tags do not allow the signature: `k=` or `h=` reject
+    /// its `a=` ("key-algorithm"), or `s=` or `t=` reject its use ("key-record")
+    KeyRecord(KeyRecordError),
+    /// `b=` is not valid base64
+    InvalidBase64,
+    /// The RSA check of the canonicalized header hash failed
+    Rsa(RsaError),
+}
+
+impl DkimVerifyError {
+    /// Short stable name of the failing step
+    pub fn step(&self) -> &'static str {
+        match self {
+            Self::Signature(_) => "signature",
+            Self::UnsupportedAlgorithm(_) => "algorithm",
+            Self::Expired { .. } => "expiration",
+            Self::KeyRecord(
+                KeyRecordError::KeyTypeMismatch { .. } | KeyRecordError::HashNotAllowed { .. },
+            ) => "key-algorithm",
+            Self::KeyRecord(_) => "key-record",
+            Self::InvalidBase64 => "base64",
+            Self::Rsa(RsaError::KeyTooSmall(_)) => "key",
+            Self::Rsa(RsaError::SignatureOutOfRange | RsaError::InvalidPadding) => "rsa",
+            Self::Rsa(RsaError::UnexpectedDigestAlgorithm) => "digest-info",
+            Self::Rsa(RsaError::DigestMismatch) => "header-hash",
+        }
+    }
+}
+
+impl fmt::Display for DkimVerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signature(reason) => write!(f, "{}", reason),
+            Self::UnsupportedAlgorithm(a) => {
+                write!(f, "Unsupported DKIM algorithm '{}' (expected rsa-sha256)", a)
+            }
+            Self::Expired { expiration, now } => write!(
+                f,
+                "DKIM signature expired at {} (x=), before the verification time {}",
+                expiration, now
+            ),
+            Self::KeyRecord(e) => write!(f, "{}", e),
+            Self::InvalidBase64 => write!(f, "DKIM signature (b=) is not valid base64"),
+            Self::Rsa(RsaError::DigestMismatch) => write!(
+                f,
+                "Header hash mismatch: the signed headers were modified after signing"
+            ),
+            Self::Rsa(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DkimVerifyError {}
+
+/// Canonicalized signed header data of the DKIM-Signature at `field_index`
+pub fn signed_header_data(
+    message: &ParsedMessage,
+    field_index: usize,
+    signature: &DkimSignature,
+) -> Vec<u8> {
+    let (header_algorithm, _) = signature.canonicalization();
+    let signed_headers: Vec<&str> = signature.h.iter().map(String::as_str).collect();
+    canonicalization::canonicalized_signed_data(
+        &message.headers,
+        &message.headers[field_index],
+        &signed_headers,
+        header_algorithm,
+    )
+}
+
+/// Check the RSA-SHA256 signature over the headers of `email` under the key
+/// of `record`, after checking that the record's tags allow the signature.
+/// With a verification time `now` (unix seconds), a signature whose `x=` is
+/// earlier has expired (RFC 6376 §3.5).
+///
+/// Returns the signature that was verified; the body hash is not checked.
+pub fn verify_dkim(
+    email: &[u8],
+    record: &DkimKeyRecord,
+    policy: &SignaturePolicy,
+    now: Option<u64>,
+) -> Result<DkimSignature, DkimVerifyError> {
+    let message = canonicalization::parse_message(email);
+    let (index, signature) =
+        select_signature(&message, policy).map_err(DkimVerifyError::Signature)?;
+    if let (Some(expiration), Some(now)) = (signature.x, now) {
+        if now > expiration {
+            return Err(DkimVerifyError::Expired { expiration, now });
+        }
+    }
+    record
+        .check_signature(&signature)
+        .map_err(DkimVerifyError::KeyRecord)?;
+    verify_signature_at(&message, index, &signature, &record.public_key)?;
+    Ok(signature)
+}
+
+/// Check the DKIM-Signature at `field_index` of an already parsed message
+pub fn verify_signature_at(
+    message: &ParsedMessage,
+    field_index: usize,
+    signature: &DkimSignature,
+    key: &RsaPublicKey,
+) -> Result<(), DkimVerifyError> {
+    if !signature.a.eq_ignore_ascii_case("rsa-sha256") {
+        return Err(DkimVerifyError::UnsupportedAlgorithm(signature.a.clone()));
+    }
+    let signature_bytes = general_purpose::STANDARD
+        .decode(&signature.b)
+        .map_err(|_| DkimVerifyError::InvalidBase64)?;
+
+    let digest = sha256(&signed_header_data(message, field_index, signature));
+    verify_pkcs1v15_sha256(key, &digest, &signature_bytes).map_err(DkimVerifyError::Rsa)
+}
+
+/// Case-insensitive check that `domain` equals `parent` or is one of its subdomains
+pub(crate) fn is_same_or_subdomain(domain: &str, parent: &str) -> bool {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    let parent = parent.trim_end_matches('.').to_ascii_lowercase();
+    domain == parent || domain.ends_with(&format!(".{}", parent))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// DKIM-Signature value from the Gmail sample in the repository root
+    const GMAIL_SIGNATURE: &str = " v=1; a=rsa-sha256; c=relaxed/relaxed;\r\n\
+        \x20       d=g.bracu.ac.bd; s=google; t=1767592020; x=1768196820; darn=g.bracu.ac.bd;\r\n\
+        \x20       h=list-unsubscribe:list-archive:list-help\r\n\
+        \x20        :to:subject:message-id:date:from:mime-version;\r\n\
+        \x20       bh=uBEaosQZ1V2HaEBdkMYu63suQND5OiUbBST6TnLNtSU=;\r\n\
+        \x20       b=EZfDo0Z7E/KJkFEVEBED++09yMWatdmYzqyLTXqwYOkYJfwQjeU388gqEV93mhl0sZ\r\n\
+        \x20        /VuXxeppNajdGoshTfCTcOYBOJDCZs8X1Vi0zyFar74xYNAdIdhrkBJQk1kSJnf9M6e+\r\n";
+
+    #[test]
+    fn test_parse_gmail_signature() {
+        let sig = DkimSignature::parse(GMAIL_SIGNATURE).unwrap();
+        assert_eq!(sig.a, "rsa-sha256");
+        assert_eq!(sig.d, "g.bracu.ac.bd");
+        assert_eq!(sig.s, "google");
+        assert_eq!(sig.t, Some(1767592020));
+        assert_eq!(sig.x, Some(1768196820));
+        assert_eq!(sig.l, None);
+        assert_eq!(sig.h.len(), 9);
+        assert_eq!(sig.h[3], "to");
+        assert_eq!(sig.bh, "uBEaosQZ1V2HaEBdkMYu63suQND5OiUbBST6TnLNtSU=");
+        assert!(sig.b.starts_with("EZfDo0Z7E/KJ"));
+        assert!(sig.b.ends_with("zyFar74xYNAdIdhrkBJQk1kSJnf9M6e+"));
+        assert_eq!(
+            sig.canonicalization(),
+            (Canonicalization::Relaxed, Canonicalization::Relaxed)
+        );
+    }
+
+    #[test]
+    fn test_selector_is_not_read_from_other_tags() {
+        // The old `s=([^;\s]+)` regex matched inside `bh=` here
+        let sig =
+            DkimSignature::parse("v=1; a=rsa-sha256; d=x.com; h=from; bh=as=AAA=; s=sel; b=QQ==")
+                .unwrap();
+        assert_eq!(sig.s, "sel");
+        assert_eq!(sig.bh, "as=AAA=");
+        assert_eq!(sig.c, "simple/simple");
+    }
+
+    #[test]
+    fn test_select_signature() {
+        let email = "DKIM-Signature: v=1; a=rsa-sha256; d=gappssmtp.com; s=20230601; h=from; bh=AA==; b=QQ==\r\n\
+DKIM-Signature: v=1; a=rsa-sha256; d=bracu.ac.bd; s=google; h=from; bh=AA==; b=Qg==\r\n\
+DKIM-Signature: v=1; a=rsa-sha256; d=g.bracu.ac.bd; s=google; h=from; bh=AA==; b=Qw==\r\n\
+From: \"Registrar\" <registrar@G.bracu.ac.bd>\r\n\r\nbody\r\n";
+        let message = crate::canonicalization::parse_message(email.as_bytes());
+        assert_eq!(signature_fields(&message).len(), 3);
+        assert_eq!(from_domain(&message).as_deref(), Some("g.bracu.ac.bd"));
+
+        // Only the exact From domain aligns, not the parent domain that appears first
+        let (index, sig) = select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+        assert_eq!((index, sig.b.as_str()), (2, "Qw=="));
+        for parent in ["bracu.ac.bd", "ac.bd", "bd"] {
+            let only_parent = email.replace("d=g.bracu.ac.bd", &format!("d={}", parent));
+            let message = crate::canonicalization::parse_message(only_parent.as_bytes());
+            let err = select_signature(&message, &SignaturePolicy::FromAligned).unwrap_err();
+            assert!(matches!(err, SignatureError::NoMatch { .. }), "{}", parent);
+        }
+
+        let target = SignaturePolicy::Domain("gappssmtp.com".to_string());
+        assert_eq!(select_signature(&message, &target).unwrap().0, 0);
+
+        let err = select_signature(&message, &SignaturePolicy::Domain("gmail.com".to_string()))
+            .unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("d=gmail.com") && err.contains("d=gappssmtp.com"), "{}", err);
+
+        let unrelated = email.replace("G.bracu.ac.bd", "example.org");
+        let message = crate::canonicalization::parse_message(unrelated.as_bytes());
+        let err = select_signature(&message, &SignaturePolicy::FromAligned).unwrap_err();
+        assert!(matches!(&err, SignatureError::NoMatch { available, .. } if available.len() == 3));
+        assert!(err.to_string().contains("example.org"), "{}", err);
+    }
+
+    #[test]
+    fn test_prepended_from_is_rejected() {
+        let email = "DKIM-Signature: v=1; a=rsa-sha256; d=g.bracu.ac.bd; s=google; h=from; bh=AA==; b=Qw==\r\n\
+DKIM-Signature: v=1; a=rsa-sha256; d=evil.com; s=s1; h=from; bh=AA==; b=QQ==\r\n\
+From: Registrar <registrar@g.bracu.ac.bd>\r\n\
+From: Mallory <mallory@evil.com>\r\n\r\nbody\r\n";
+        let message = crate::canonicalization::parse_message(email.as_bytes());
+        // The signed instance is the bottom one
+        assert_eq!(from_domain(&message).as_deref(), Some("evil.com"));
+        for policy in [
+            SignaturePolicy::FromAligned,
+            SignaturePolicy::Domain("g.bracu.ac.bd".to_string()),
+        ] {
+            assert_eq!(
+                select_signature(&message, &policy),
+                Err(SignatureError::MultipleFrom(2))
+            );
+        }
+    }
+
+    /// Unsigned test email; `b=` is left empty for [`sign_email`]
+    pub(crate) const TEST_EMAIL: &str = "Received: by mail.example.com\r\n\
+DKIM-Signature: v=1; a=rsa-sha256; c=relaxed/relaxed; d=example.com; s=test;\r\n\
+\th=from:to:subject; bh=YJC6sFHGk5paEfivUv71xLRk9l6/Mpywbes6g++OLys=;\r\n\
+\tb=\r\n\
+From: Alice <alice@example.com>\r\n\
+To: bob@example.org\r\n\
+Subject:   Exam   schedule\r\n\
+\r\n\
+Hello Bob\r\n";
+
+    /// Sign `email` with the RSA test key, filling in its empty `b=`
+    pub(crate) fn sign_email(email: &str) -> String {
+        let message = crate::canonicalization::parse_message(email.as_bytes());
+        let (index, signature) = select_signature(&message, &SignaturePolicy::FromAligned).unwrap();
+        let b = crate::rsa::tests::sign(&signed_header_data(&message, index, &signature));
+        email.replacen("\tb=\r\n", &format!("\tb={}\r\n", general_purpose::STANDARD.encode(b)), 1)
+    }
+
+    #[test]
+    fn test_verify_dkim() {
+        let (key, _) = crate::rsa::tests::test_key();
+        let record = DkimKeyRecord::from(key.clone());
+        let verify = |email: &str, record: &DkimKeyRecord, now: Option<u64>| {
+            verify_dkim(email.as_bytes(), record, &SignaturePolicy::FromAligned, now)
+        };
+        let email = sign_email(TEST_EMAIL);
+        let signature = verify(&email, &record, None).unwrap();
+        assert_eq!(signature.s, "test");
+
+        // Relaxed canonicalization tolerates whitespace changes but not content changes
+        let rewrapped = email.replace("Subject:   Exam   schedule", "Subject: Exam\r\n  schedule");
+        assert!(verify(&rewrapped, &record, None).is_ok());
+        let err = verify(&email.replace("Exam", "Quiz"), &record, None).unwrap_err();
+        assert_eq!(err, DkimVerifyError::Rsa(RsaError::DigestMismatch));
+        assert_eq!(err.step(), "header-hash");
+
+        let wrong_key = RsaPublicKey::from_modulus(&key.modulus - 2u32).into();
+        assert_eq!(verify(&email, &wrong_key, None).unwrap_err().step(), "rsa");
+
+        let sha1 = email.replace("a=rsa-sha256", "a=rsa-sha1");
+        assert_eq!(verify(&sha1, &record, None).unwrap_err().step(), "algorithm");
+
+        // A valid signature is still rejected when the record does not allow it
+        let sha1_only = DkimKeyRecord {
+            hash_algorithms: vec!["sha1".to_string()],
+            ..record.clone()
+        };
+        let err = verify(&email, &sha1_only, None).unwrap_err();
+        assert!(matches!(err, DkimVerifyError::KeyRecord(KeyRecordError::HashNotAllowed { .. })));
+        assert_eq!(err.step(), "key-algorithm");
+        let testing = DkimKeyRecord {
+            flags: vec!["y".to_string()],
+            ..record.clone()
+        };
+        assert_eq!(verify(&email, &testing, None).unwrap_err().step(), "key-record");
+
+        assert_eq!(verify(TEST_EMAIL, &record, None).unwrap_err().step(), "rsa");
+
+        // x= only counts when the caller gives a verification time
+        let expiring =
+            sign_email(&TEST_EMAIL.replace("s=test;", "s=test; t=1700000000; x=1700086400;"));
+        assert!(verify(&expiring, &record, None).is_ok());
+        assert!(verify(&expiring, &record, Some(1_700_086_400)).is_ok());
+        let err = verify(&expiring, &record, Some(1_700_086_401)).unwrap_err();
+        assert_eq!(
+            err,
+            DkimVerifyError::Expired {
+                expiration: 1_700_086_400,
+                now: 1_700_086_401
+            }
+        );
+        assert_eq!(err.step(), "expiration");
+    }
+
+    #[test]
+    fn test_tag_list_errors() {
+        let base = "v=1; a=rsa-sha256; d=x.com; s=sel; h=from; bh=AA==; b=QQ==";
+        assert!(DkimSignature::parse(base).is_ok());
+        assert!(DkimSignature::parse(&format!("{};", base)).is_ok());
+
+        assert_eq!(
+            DkimSignature::parse(&format!("{}; d=y.com", base)),
+            Err(DkimTagError::DuplicateTag("d".to_string()))
+        );
+        assert_eq!(
+            DkimSignature::parse("v=1; a=rsa-sha256; d=x.com; h=from; bh=AA==; b=QQ=="),
+            Err(DkimTagError::MissingTag("s"))
+        );
+        assert_eq!(
+            DkimSignature::parse(&format!("{};; t=1", base)),
+            Err(DkimTagError::Malformed(String::new()))
+        );
+        assert!(matches!(
+            DkimSignature::parse(&format!("{}; l=ten", base)),
+            Err(DkimTagError::InvalidValue { tag: "l", .. })
+        ));
+        assert!(matches!(
+            DkimSignature::parse(&base.replace("h=from", "h=to:subject")),
+            Err(DkimTagError::InvalidValue { tag: "h", .. })
+        ));
+        assert!(matches!(
+            DkimSignature::parse(&format!("{}; i=user@evil.com", base)),
+            Err(DkimTagError::InvalidValue { tag: "i", .. })
+        ));
+        assert!(DkimSignature::parse(&format!("{}; i=@mail.x.com", base)).is_ok());
+    }
+}
@@ -0,0 +1,393 @@
+// ============================================================================
+// DKIM key records from DNS TXT (RFC 6376 §3.6.1)
+// ============================================================================
+
+use crate::dkim::{parse_tag_list, strip_fws, DkimSignature, DkimTagError};
+use crate::rsa::RsaPublicKey;
+use base64::{engine::general_purpose, Engine as _};
+use num_bigint::BigUint;
+use std::fmt;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Error produced while reading a DKIM key record or public key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DkimKeyError {
+    /// The record is not a valid tag-list or lacks `p=`
+    Tag(DkimTagError),
+    /// `p=` is empty: the key has been revoked by the domain owner
+    Revoked,
+    /// `v=` is present but is not "DKIM1"
+    UnsupportedVersion(String),
+    /// `k=` names a key type other than rsa
+    UnsupportedKeyType(String),
+    /// The key is not valid base64
+    InvalidBase64,
+    /// The key is not a DER SubjectPublicKeyInfo or RSAPublicKey
+    InvalidDer(String),
+}
+
+impl fmt::Display for DkimKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag(e) => write!(f, "{}", e),
+            Self::Revoked => write!(f, "DKIM key has been revoked (empty p=)"),
+            Self::UnsupportedVersion(v) => {
+                write!(f, "Unsupported DKIM key record version '{}' (expected DKIM1)", v)
+            }
+            Self::UnsupportedKeyType(k) => {
+                write!(f, "Unsupported DKIM key type '{}' (expected rsa)", k)
+            }
+            Self::InvalidBase64 => write!(f, "DKIM public key is not valid base64"),
+            Self::InvalidDer(reason) => write!(f, "Invalid DKIM public key: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DkimKeyError {}
+
+/// A DKIM-Signature that the tags of its key record do not allow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyRecordError {
+    /// The key type of `a=` is not the record's `k=`
+    KeyTypeMismatch { algorithm: String, key_type: String },
+    /// The hash of `a=` is not listed in `h=`
+    HashNotAllowed { hash: String, allowed: Vec<String> },
+    /// `s=` lists neither "email" nor "*"
+    ServiceNotAllowed(Vec<String>),
+    /// `t=s` and the domain of `i=` is not exactly `d=`
+    StrictIdentity { identity: String, domain: String },
+    /// `t=y`: the domain is only testing DKIM, so the signature proves nothing
+    Testing,
+}
+
+impl fmt::Display for KeyRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeyTypeMismatch { algorithm, key_type } => write!(
+                f,
+                "DKIM key record has k={} but the signature uses a={}",
+                key_type, algorithm
+            ),
+            Self::HashNotAllowed { hash, allowed } => write!(
+                f,
+                "DKIM key record allows h={} but the signature uses {}",
+                allowed.join(":"),
+                hash
+            ),
+            Self::ServiceNotAllowed(services) => write!(
+                f,
+                "DKIM key record is not for email (s={})",
+                services.join(":")
+            ),
+            Self::StrictIdentity { identity, domain } => write!(
+                f,
+                "DKIM key record has t=s but i={} is not in d={} itself",
+                identity, domain
+            ),
+            Self::Testing => write!(f, "DKIM key record has t=y (domain is testing DKIM)"),
+        }
+    }
+}
+
+impl std::error::Error for KeyRecordError {}
+
+impl From<DkimTagError> for DkimKeyError {
+    fn from(e: DkimTagError) -> Self {
+        Self::Tag(e)
+    }
+}
+
+/// A parsed `v=DKIM1; k=rsa; p=...` record
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DkimKeyRecord {
+    /// Key type (`k=`), "rsa" when absent
+    pub key_type: String,
+    /// Acceptable hash algorithms (`h=`); empty means any
+    pub hash_algorithms: Vec<String>,
+    /// Service types (`s=`), `["*"]` when absent
+    pub service_types: Vec<String>,
+    /// Flags (`t=`), e.g. "y" for testing or "s" for strict identity
+    pub flags: Vec<String>,
+    #[cfg_attr(feature = "wasm", wasm_bindgen(skip))]
+    pub public_key: RsaPublicKey,
+}
+
+impl DkimKeyRecord {
+    /// Parse TXT record text. Quoted character-strings, as printed by `dig`,
+    /// are joined first.
+    pub fn parse(txt: &str) -> Result<Self, DkimKeyError> {
+        let txt = join_character_strings(txt);
+        let tags = parse_tag_list(&txt)?;
+        let get = |name: &str| {
+            tags.iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let list = |value: &str| -> Vec<String> {
+            value
+                .split(':')
+                .map(|item| strip_fws(item).to_ascii_lowercase())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+
+        if let Some(v) = get("v") {
+            if v != "DKIM1" || tags[0].0 != "v" {
+                return Err(DkimKeyError::UnsupportedVersion(v.to_string()));
+            }
+        }
+        let key_type = get("k").unwrap_or("rsa").to_ascii_lowercase();
+        if key_type != "rsa" {
+            return Err(DkimKeyError::UnsupportedKeyType(key_type));
+        }
+
+        let p = get("p").ok_or(DkimTagError::MissingTag("p"))?;
+        let p = strip_fws(p);
+        if p.is_empty() {
+            return Err(DkimKeyError::Revoked);
+        }
+        let der = general_purpose::STANDARD
+            .decode(&p)
+            .map_err(|_| DkimKeyError::InvalidBase64)?;
+
+        Ok(Self {
+            key_type,
+            hash_algorithms: get("h").map(list).unwrap_or_default(),
+            service_types: get("s").map(list).unwrap_or_else(|| vec!["*".to_string()]),
+            flags: get("t").map(list).unwrap_or_default(),
+            public_key: RsaPublicKey::from_der(&der).map_err(DkimKeyError::InvalidDer)?,
+        })
+    }
+
+    /// Whether `h=` permits the given hash ("sha256")
+    pub fn allows_hash(&self, hash: &str) -> bool {
+        self.hash_algorithms.is_empty()
+            || self.hash_algorithms.iter().any(|h| h.eq_ignore_ascii_case(hash))
+    }
+
+    /// Whether the domain is only testing DKIM (`t=y`)
+    pub fn is_testing(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "y")
+    }
+
+    /// Whether the record may be used for email (`s=` is "*" or lists "email")
+    pub fn allows_email(&self) -> bool {
+        self.service_types.iter().any(|s| s == "*" || s == "email")
+    }
+
+    /// Whether `i=` must be in `d=` itself rather than a subdomain (`t=s`)
+    pub fn is_strict(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "s")
+    }
+
+    /// Check that the record allows `signature` (RFC 6376 §3.6.1, §6.1.2)
+    pub fn check_signature(&self, signature: &DkimSignature) -> Result<(), KeyRecordError> {
+        let (key_type, hash) = signature.a.split_once('-').unwrap_or((&signature.a, ""));
+        if !key_type.eq_ignore_ascii_case(&self.key_type) {
+            return Err(KeyRecordError::KeyTypeMismatch {
+                algorithm: signature.a.clone(),
+                key_type: self.key_type.clone(),
+            });
+        }
+        if !self.allows_hash(hash) {
+            return Err(KeyRecordError::HashNotAllowed {
+                hash: hash.to_ascii_lowercase(),
+                allowed: self.hash_algorithms.clone(),
+            });
+        }
+        if !self.allows_email() {
+            return Err(KeyRecordError::ServiceNotAllowed(self.service_types.clone()));
+        }
+        if self.is_strict() {
+            if let Some(identity) = &signature.i {
+                let domain = identity.rsplit_once('@').map_or("", |(_, domain)| domain);
+                if !domain.eq_ignore_ascii_case(&signature.d) {
+                    return Err(KeyRecordError::StrictIdentity {
+                        identity: identity.clone(),
+                        domain: signature.d.clone(),
+                    });
+                }
+            }
+        }
+        if self.is_testing() {
+            return Err(KeyRecordError::Testing);
+        }
+        Ok(())
+    }
+}
+
+impl From<RsaPublicKey> for DkimKeyRecord {
+    /// A record with the default tags (`k=rsa`, any hash, `s=*`, no flags)
+    fn from(public_key: RsaPublicKey) -> Self {
+        Self {
+            key_type: "rsa".to_string(),
+            hash_algorithms: Vec::new(),
+            service_types: vec!["*".to_string()],
+            flags: Vec::new(),
+            public_key,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DkimKeyRecord {
+    /// RSA modulus as a decimal string
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn modulus(&self) -> String {
+        self.public_key.modulus.to_string()
+    }
+
+    /// RSA public exponent as a decimal string
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn exponent(&self) -> String {
+        self.public_key.exponent.to_string()
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn key_bits(&self) -> u64 {
+        self.public_key.modulus.bits()
+    }
+}
+
+/// `"v=DKIM1; k=rsa; " "p=MIIB..."` becomes `v=DKIM1; k=rsa; p=MIIB...`
+fn join_character_strings(txt: &str) -> String {
+    let txt = txt.trim();
+    if !txt.starts_with('"') {
+        return txt.to_string();
+    }
+    txt.split('"').skip(1).step_by(2).collect()
+}
+
+/// Read a key record from any of the forms callers have at hand: a whole
+/// TXT record (anything containing `p=`), a base64 DER SubjectPublicKeyInfo
+/// or RSAPublicKey, or a base64 big-endian modulus. Bare keys get the
+/// default tags.
+///
+/// Decoded bytes that do not start with a DER SEQUENCE are taken as a bare
+/// modulus with exponent 65537.
+pub fn parse_key_record(input: &str) -> Result<DkimKeyRecord, DkimKeyError> {
+    if input.contains("p=") {
+        return DkimKeyRecord::parse(input);
+    }
+    let bytes = general_purpose::STANDARD
+        .decode(strip_fws(input))
+        .map_err(|_| DkimKeyError::InvalidBase64)?;
+    match bytes.first() {
+        Some(0x30) => RsaPublicKey::from_der(&bytes).map_err(DkimKeyError::InvalidDer),
+        Some(_) => Ok(RsaPublicKey::from_modulus(BigUint::from_bytes_be(&bytes))),
+        None => Err(DkimKeyError::Revoked),
+    }
+    .map(DkimKeyRecord::from)
+}
+
+/// The RSA public key of `parse_key_record`, for callers that only need
+/// the key
+pub fn parse_public_key(input: &str) -> Result<RsaPublicKey, DkimKeyError> {
+    parse_key_record(input).map(|record| record.public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa::tests::test_key;
+
+    const RECORD: &str = include_str!("../testdata/dkim_test_record.txt");
+
+    #[test]
+    fn test_parse_key_record() {
+        let (key, _) = test_key();
+        let record = DkimKeyRecord::parse(RECORD).unwrap();
+        assert_eq!(record.public_key, key);
+        assert_eq!(record.key_type, "rsa");
+        assert_eq!(record.service_types, vec!["*"]);
+        assert_eq!(record.key_bits(), 2048);
+        assert_eq!(record.exponent(), "65537");
+        assert!(record.allows_hash("sha256") && !record.is_testing());
+
+        // dig prints long records as several quoted strings
+        let p = RECORD.trim().rsplit_once("p=").unwrap().1;
+        let (head, tail) = p.split_at(100);
+        let quoted = format!("\"v=DKIM1; k=rsa; t=y; h=sha256; p={}\" \"{}\"", head, tail);
+        let record = DkimKeyRecord::parse(&quoted).unwrap();
+        assert_eq!(record.public_key, key);
+        assert!(record.is_testing());
+        assert!(record.allows_hash("sha256") && !record.allows_hash("sha1"));
+    }
+
+    #[test]
+    fn test_key_record_errors() {
+        assert_eq!(DkimKeyRecord::parse("v=DKIM1; k=rsa; p="), Err(DkimKeyError::Revoked));
+        assert_eq!(
+            DkimKeyRecord::parse("v=DKIM1; k=rsa"),
+            Err(DkimKeyError::Tag(DkimTagError::MissingTag("p")))
+        );
+        assert_eq!(
+            DkimKeyRecord::parse("v=DKIM1; k=ed25519; p=AAAA"),
+            Err(DkimKeyError::UnsupportedKeyType("ed25519".to_string()))
+        );
+        assert!(matches!(
+            DkimKeyRecord::parse("k=rsa; v=DKIM1; p=AAAA"),
+            Err(DkimKeyError::UnsupportedVersion(_))
+        ));
+        assert_eq!(DkimKeyRecord::parse("p=!!!"), Err(DkimKeyError::InvalidBase64));
+        assert!(matches!(DkimKeyRecord::parse("p=MAA="), Err(DkimKeyError::InvalidDer(_))));
+    }
+
+    #[test]
+    fn test_parse_public_key_forms() {
+        let (key, _) = test_key();
+        let p = RECORD.trim().rsplit_once("p=").unwrap().1;
+        assert_eq!(parse_public_key(RECORD).unwrap(), key);
+        assert_eq!(parse_public_key(p).unwrap(), key);
+
+        let modulus = general_purpose::STANDARD.encode(key.modulus.to_bytes_be());
+        assert_eq!(parse_public_key(&modulus).unwrap(), key);
+        assert_eq!(parse_public_key("p="), Err(DkimKeyError::Revoked));
+        assert_eq!(parse_key_record(&modulus).unwrap(), DkimKeyRecord::from(key));
+    }
+
+    #[test]
+    fn test_check_signature() {
+        let p = RECORD.trim().rsplit_once("p=").unwrap().1;
+        let record =
+            |tags: &str| DkimKeyRecord::parse(&format!("v=DKIM1; {}p={}", tags, p)).unwrap();
+        let mut signature = DkimSignature::parse(
+            "v=1; a=rsa-sha256; d=g.bracu.ac.bd; s=google; h=from; bh=AAAA; b=AAAA",
+        )
+        .unwrap();
+
+        assert_eq!(record("").check_signature(&signature), Ok(()));
+        assert_eq!(record("h=sha1:sha256; s=email:tlsrpt; ").check_signature(&signature), Ok(()));
+        assert_eq!(
+            record("h=sha1; ").check_signature(&signature),
+            Err(KeyRecordError::HashNotAllowed {
+                hash: "sha256".to_string(),
+                allowed: vec!["sha1".to_string()],
+            })
+        );
+        assert_eq!(
+            record("s=tlsrpt; ").check_signature(&signature),
+            Err(KeyRecordError::ServiceNotAllowed(vec!["tlsrpt".to_string()]))
+        );
+        assert_eq!(record("t=y; ").check_signature(&signature), Err(KeyRecordError::Testing));
+
+        let mut ed25519 = signature.clone();
+        ed25519.a = "ed25519-sha256".to_string();
+        assert!(matches!(
+            record("").check_signature(&ed25519),
+            Err(KeyRecordError::KeyTypeMismatch { .. })
+        ));
+
+        // t=s: i= must be in d= itself, not a subdomain of it
+        signature.i = Some("@G.bracu.ac.bd".to_string());
+        assert_eq!(record("t=s; ").check_signature(&signature), Ok(()));
+        signature.i = Some("student@mail.g.bracu.ac.bd".to_string());
+        assert_eq!(record("").check_signature(&signature), Ok(()));
+        assert!(matches!(
+            record("t=s; ").check_signature(&signature),
+            Err(KeyRecordError::StrictIdentity { .. })
+        ));
+    }
+}
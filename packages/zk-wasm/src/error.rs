@@ -0,0 +1,333 @@
+// ============================================================================
+// Errors
+// ============================================================================
+
+use crate::dkim::{DkimVerifyError, SignatureError};
+use crate::dkim_key::DkimKeyError;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Error returned by every fallible export.
+///
+/// In JavaScript it is thrown as an `Error` whose `message` is the `Display`
+/// text, with a stable `code` (e.g. `"FROM_HEADER_TOO_EARLY"`) to branch on
+/// and a `details` object holding the values behind the message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ZkWasmError {
+    /// The email is not valid UTF-8
+    InvalidUtf8,
+    /// The email has no DKIM-Signature field
+    NoDkimSignature,
+    /// Every DKIM-Signature failed to parse
+    InvalidDkimSignature(String),
+    /// No DKIM-Signature satisfies the signature policy
+    NoMatchingSignature {
+        wanted: String,
+        available: Vec<String>,
+    },
+    NoFromHeader,
+    /// The From header holds no email address
+    NoFromAddress,
+    /// The message has more than one From field
+    MultipleFromHeaders { count: usize },
+    /// The From header starts so early that the rest of the header does not
+    /// fit in the circuit after precomputing the blocks before it
+    FromHeaderTooEarly {
+        from_index: usize,
+        header_length: usize,
+        max_remaining: usize,
+    },
+    /// The SHA-256 padded header is longer than the circuit's buffer
+    HeaderTooLong {
+        length: usize,
+        padded_length: usize,
+        max: usize,
+    },
+    /// A base64 value (`field`: "b", "p" or "pubkey") does not decode
+    InvalidBase64 { field: &'static str },
+    /// A DKIM public key is needed but none was given
+    MissingKey,
+    InvalidKey(String),
+    /// The DNS record has an empty `p=`
+    KeyRevoked,
+    /// The DKIM signature does not verify under the key; `step` as in
+    /// `DkimVerifyError::step`
+    DkimVerificationFailed { step: &'static str, reason: String },
+    UnsupportedKeySize { bits: usize },
+    /// A value does not fit the limb layout
+    LimbLayout(String),
+    /// An argument is out of range or not a valid field element
+    InvalidInput(String),
+    InvalidRegistry(String),
+    /// No verifying key was loaded into the verifier
+    KeyNotLoaded,
+    ProvingKeyNotLoaded,
+    CircuitNotLoaded,
+    MalformedVerifyingKey(String),
+    MalformedProvingKey(String),
+    /// The circuit `.wasm` or `.r1cs` could not be loaded
+    MalformedCircuit(String),
+    MalformedProof(String),
+    MalformedPublicSignals(String),
+    PublicSignalCount { expected: usize, actual: usize },
+    /// The Groth16 check could not be completed (e.g. a degenerate pairing)
+    Verification(String),
+    /// Witness calculation failed or the witness violates a constraint
+    Witness(String),
+    Proving(String),
+}
+
+impl ZkWasmError {
+    /// Stable identifier of the error kind, never reworded
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidUtf8 => "INVALID_UTF8",
+            Self::NoDkimSignature => "NO_DKIM_SIGNATURE",
+            Self::InvalidDkimSignature(_) => "INVALID_DKIM_SIGNATURE",
+            Self::NoMatchingSignature { .. } => "NO_MATCHING_SIGNATURE",
+            Self::NoFromHeader => "NO_FROM_HEADER",
+            Self::NoFromAddress => "NO_FROM_ADDRESS",
+            Self::MultipleFromHeaders { .. } => "MULTIPLE_FROM_HEADERS",
+            Self::FromHeaderTooEarly { .. } => "FROM_HEADER_TOO_EARLY",
+            Self::HeaderTooLong { .. } => "HEADER_TOO_LONG",
+            Self::InvalidBase64 { .. } => "INVALID_BASE64",
+            Self::MissingKey => "MISSING_KEY",
+            Self::InvalidKey(_) => "INVALID_KEY",
+            Self::KeyRevoked => "KEY_REVOKED",
+            Self::DkimVerificationFailed { .. } => "DKIM_VERIFICATION_FAILED",
+            Self::UnsupportedKeySize { .. } => "UNSUPPORTED_KEY_SIZE",
+            Self::LimbLayout(_) => "LIMB_LAYOUT",
+            Self::InvalidInput(_) => "INVALID_INPUT",
+            Self::InvalidRegistry(_) => "INVALID_REGISTRY",
+            Self::KeyNotLoaded => "KEY_NOT_LOADED",
+            Self::ProvingKeyNotLoaded => "PROVING_KEY_NOT_LOADED",
+            Self::CircuitNotLoaded => "CIRCUIT_NOT_LOADED",
+            Self::MalformedVerifyingKey(_) => "MALFORMED_VERIFYING_KEY",
+            Self::MalformedProvingKey(_) => "MALFORMED_PROVING_KEY",
+            Self::MalformedCircuit(_) => "MALFORMED_CIRCUIT",
+            Self::MalformedProof(_) => "MALFORMED_PROOF",
+            Self::MalformedPublicSignals(_) => "MALFORMED_PUBLIC_SIGNALS",
+            Self::PublicSignalCount { .. } => "PUBLIC_SIGNAL_COUNT",
+            Self::Verification(_) => "VERIFICATION_FAILED",
+            Self::Witness(_) => "WITNESS_FAILED",
+            Self::Proving(_) => "PROVING_FAILED",
+        }
+    }
+
+    /// The values behind the message as a JSON object; empty when there are none
+    pub fn details(&self) -> Value {
+        match self {
+            Self::NoMatchingSignature { wanted, available } => {
+                json!({ "wanted": wanted, "available": available })
+            }
+            Self::FromHeaderTooEarly {
+                from_index,
+                header_length,
+                max_remaining,
+            } => json!({
+                "from_index": from_index,
+                "header_length": header_length,
+                "max_remaining": max_remaining,
+            }),
+            Self::HeaderTooLong {
+                length,
+                padded_length,
+                max,
+            } => json!({ "length": length, "padded_length": padded_length, "max": max }),
+            Self::InvalidBase64 { field } => json!({ "field": field }),
+            Self::DkimVerificationFailed { step, reason } => {
+                json!({ "step": step, "reason": reason })
+            }
+            Self::UnsupportedKeySize { bits } => json!({ "bits": bits }),
+            Self::MultipleFromHeaders { count } => json!({ "count": count }),
+            Self::PublicSignalCount { expected, actual } => {
+                json!({ "expected": expected, "actual": actual })
+            }
+            Self::InvalidDkimSignature(reason)
+            | Self::InvalidKey(reason)
+            | Self::LimbLayout(reason)
+            | Self::InvalidInput(reason)
+            | Self::InvalidRegistry(reason)
+            | Self::MalformedVerifyingKey(reason)
+            | Self::MalformedProvingKey(reason)
+            | Self::MalformedCircuit(reason)
+            | Self::MalformedProof(reason)
+            | Self::MalformedPublicSignals(reason)
+            | Self::Verification(reason)
+            | Self::Witness(reason)
+            | Self::Proving(reason) => json!({ "reason": reason }),
+            Self::InvalidUtf8
+            | Self::NoDkimSignature
+            | Self::NoFromHeader
+            | Self::NoFromAddress
+            | Self::MissingKey
+            | Self::KeyRevoked
+            | Self::KeyNotLoaded
+            | Self::ProvingKeyNotLoaded
+            | Self::CircuitNotLoaded => json!({}),
+        }
+    }
+}
+
+impl fmt::Display for ZkWasmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUtf8 => write!(f, "Email is not valid UTF-8"),
+            Self::NoDkimSignature => write!(f, "No DKIM-Signature header found"),
+            Self::InvalidDkimSignature(reason) => {
+                write!(f, "No valid DKIM-Signature header found: {}", reason)
+            }
+            Self::NoMatchingSignature { wanted, available } => write!(
+                f,
+                "No DKIM-Signature matches {} (signatures found for d={})",
+                wanted,
+                available.join(", d=")
+            ),
+            Self::NoFromHeader => write!(f, "No From header found in the signed headers"),
+            Self::NoFromAddress => write!(f, "No email address found in From header"),
+            Self::MultipleFromHeaders { count } => {
+                write!(f, "The email has {} From headers; only one is allowed", count)
+            }
+            Self::FromHeaderTooEarly {
+                from_index,
+                header_length,
+                max_remaining,
+            } => write!(
+                f,
+                "The From header starts at byte {} of a {}-byte header, too early to fit \
+                 the rest in {} bytes",
+                from_index, header_length, max_remaining
+            ),
+            Self::HeaderTooLong {
+                length,
+                padded_length,
+                max,
+            } => write!(
+                f,
+                "Signed header is {} bytes ({} padded), more than the circuit's {}",
+                length, padded_length, max
+            ),
+            Self::InvalidBase64 { field } => write!(f, "'{}' is not valid base64", field),
+            Self::MissingKey => write!(
+                f,
+                "No DKIM public key was given; use parse_dkim_from_email_with_key for pubkey inputs"
+            ),
+            Self::InvalidKey(reason) => write!(f, "{}", reason),
+            Self::KeyRevoked => write!(f, "DKIM key has been revoked (empty p=)"),
+            Self::DkimVerificationFailed { step, reason } => write!(
+                f,
+                "DKIM signature does not verify with this key ({}): {}",
+                step, reason
+            ),
+            Self::UnsupportedKeySize { bits } => write!(
+                f,
+                "Unsupported RSA key size {} bits (expected 1024, 2048 or 4096)",
+                bits
+            ),
+            Self::LimbLayout(reason) | Self::InvalidInput(reason) => write!(f, "{}", reason),
+            Self::InvalidRegistry(reason) => write!(f, "Invalid key registry: {}", reason),
+            Self::KeyNotLoaded => write!(f, "Verifying key not loaded"),
+            Self::ProvingKeyNotLoaded => write!(f, "Proving key not loaded"),
+            Self::CircuitNotLoaded => write!(f, "Circuit not loaded"),
+            Self::MalformedVerifyingKey(reason) => {
+                write!(f, "Failed to read verifying key: {}", reason)
+            }
+            Self::MalformedProvingKey(reason) => write!(f, "Failed to read proving key: {}", reason),
+            Self::MalformedCircuit(reason) => write!(f, "Failed to load circuit: {}", reason),
+            Self::MalformedProof(reason) => write!(f, "Failed to read proof: {}", reason),
+            Self::MalformedPublicSignals(reason) => {
+                write!(f, "Failed to read public signals: {}", reason)
+            }
+            Self::PublicSignalCount { expected, actual } => {
+                write!(f, "Expected {} public signals, got {}", expected, actual)
+            }
+            Self::Verification(reason) => write!(f, "Proof verification failed: {}", reason),
+            Self::Witness(reason) => write!(f, "Witness calculation failed: {}", reason),
+            Self::Proving(reason) => write!(f, "Proof generation failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ZkWasmError {}
+
+impl From<SignatureError> for ZkWasmError {
+    fn from(e: SignatureError) -> Self {
+        match e {
+            SignatureError::NotFound => Self::NoDkimSignature,
+            SignatureError::Invalid(e) => Self::InvalidDkimSignature(e.to_string()),
+            SignatureError::NoFromDomain => Self::NoFromAddress,
+            SignatureError::MultipleFrom(count) => Self::MultipleFromHeaders { count },
+            SignatureError::NoMatch { wanted, available } => {
+                Self::NoMatchingSignature { wanted, available }
+            }
+        }
+    }
+}
+
+impl From<DkimKeyError> for ZkWasmError {
+    fn from(e: DkimKeyError) -> Self {
+        match e {
+            DkimKeyError::Revoked => Self::KeyRevoked,
+            DkimKeyError::InvalidBase64 => Self::InvalidBase64 { field: "pubkey" },
+            other => Self::InvalidKey(other.to_string()),
+        }
+    }
+}
+
+impl From<DkimVerifyError> for ZkWasmError {
+    fn from(e: DkimVerifyError) -> Self {
+        match e {
+            DkimVerifyError::Signature(e) => e.into(),
+            DkimVerifyError::InvalidBase64 => Self::InvalidBase64 { field: "b" },
+            other => Self::DkimVerificationFailed {
+                step: other.step(),
+                reason: other.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dkim::DkimTagError;
+
+    #[test]
+    fn test_codes_and_details() {
+        let error = ZkWasmError::FromHeaderTooEarly {
+            from_index: 10,
+            header_length: 400,
+            max_remaining: 128,
+        };
+        assert_eq!(error.code(), "FROM_HEADER_TOO_EARLY");
+        assert_eq!(error.details()["max_remaining"], 128);
+        assert!(error.to_string().contains("too early"));
+
+        assert_eq!(ZkWasmError::KeyNotLoaded.details(), json!({}));
+        assert_eq!(
+            ZkWasmError::MalformedProof("bad".to_string()).details(),
+            json!({ "reason": "bad" })
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            ZkWasmError::from(SignatureError::NotFound).code(),
+            "NO_DKIM_SIGNATURE"
+        );
+        let invalid = SignatureError::Invalid(DkimTagError::MissingTag("b"));
+        assert_eq!(ZkWasmError::from(invalid).code(), "INVALID_DKIM_SIGNATURE");
+        assert_eq!(
+            ZkWasmError::from(SignatureError::MultipleFrom(2)).details(),
+            json!({ "count": 2 })
+        );
+        assert_eq!(ZkWasmError::from(DkimKeyError::Revoked), ZkWasmError::KeyRevoked);
+        assert_eq!(
+            ZkWasmError::from(DkimKeyError::InvalidBase64).code(),
+            "INVALID_BASE64"
+        );
+        let failed = ZkWasmError::from(DkimVerifyError::UnsupportedAlgorithm("rsa-sha1".into()));
+        assert_eq!(failed.details()["step"], "algorithm");
+    }
+}
@@ -0,0 +1,241 @@
+// ============================================================================
+// Pinned DKIM key registry
+// ============================================================================
+
+use crate::dkim::DkimSignature;
+use crate::dkim_key::parse_public_key;
+use crate::limbs::LimbConfig;
+use crate::poseidon::pubkey_hash;
+use crate::rsa::RsaPublicKey;
+use crate::snarkjs::field_to_decimal;
+use serde::Deserialize;
+
+/// Registry shipped with the crate (`keys/dkim_registry.json`)
+const EMBEDDED_REGISTRY: &str = include_str!("../keys/dkim_registry.json");
+
+/// A key pinned for `<selector>._domainkey.<domain>` during a validity window
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub domain: String,
+    pub selector: String,
+    pub public_key: RsaPublicKey,
+    /// First second (unix time) the key is accepted
+    pub valid_from: u64,
+    /// Last second the key is accepted; `None` while it is still in use
+    pub valid_to: Option<u64>,
+    /// Expected circuit `pubkey_hash` (decimal); `None` if the key does not
+    /// fit the circom limb layout
+    pub pubkey_hash: Option<String>,
+}
+
+impl RegistryEntry {
+    /// Whether the key was valid at `timestamp`
+    pub fn is_valid_at(&self, timestamp: u64) -> bool {
+        timestamp >= self.valid_from && self.valid_to.is_none_or(|end| timestamp <= end)
+    }
+}
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    keys: Vec<RegistryFileEntry>,
+}
+
+#[derive(Deserialize)]
+struct RegistryFileEntry {
+    domain: String,
+    selector: String,
+    public_key: String,
+    #[serde(default)]
+    valid_from: u64,
+    #[serde(default)]
+    valid_to: Option<u64>,
+    #[serde(default)]
+    pubkey_hash: Option<String>,
+}
+
+/// Allowlist of DKIM keys, looked up by the `d=`/`s=` of a signature
+#[derive(Clone, Debug, Default)]
+pub struct KeyRegistry {
+    entries: Vec<RegistryEntry>,
+}
+
+impl KeyRegistry {
+    /// The registry embedded at build time from `keys/dkim_registry.json`.
+    ///
+    /// It only holds the keys pinned there; signatures under any other
+    /// selector still need their key from DNS.
+    pub fn embedded() -> Result<Self, String> {
+        Self::from_json(EMBEDDED_REGISTRY)
+    }
+
+    /// Load a registry from `{ "keys": [{ domain, selector, public_key, valid_from, valid_to }] }`.
+    ///
+    /// An optional `pubkey_hash` per key is checked against the hash computed
+    /// from the key itself.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let file: RegistryFile =
+            serde_json::from_str(json).map_err(|e| format!("Invalid key registry JSON: {}", e))?;
+
+        let entries = file
+            .keys
+            .into_iter()
+            .map(|entry| {
+                let name = format!("{}._domainkey.{}", entry.selector, entry.domain);
+                let public_key = parse_public_key(&entry.public_key)
+                    .map_err(|e| format!("Invalid key for {}: {}", name, e))?;
+                if entry.valid_to.is_some_and(|end| end < entry.valid_from) {
+                    return Err(format!("Key for {} expires before it becomes valid", name));
+                }
+                let circom = LimbConfig::CIRCOM_2048;
+                let computed = pubkey_hash(&public_key.modulus, circom.limb_bits(), circom.num_limbs())
+                    .ok()
+                    .map(|hash| field_to_decimal(&hash));
+                if let (Some(pinned), Some(computed)) = (&entry.pubkey_hash, &computed) {
+                    if pinned.trim() != computed {
+                        return Err(format!(
+                            "Pinned pubkey_hash for {} does not match its key ({})",
+                            name, computed
+                        ));
+                    }
+                }
+                Ok(RegistryEntry {
+                    domain: entry.domain.trim_end_matches('.').to_ascii_lowercase(),
+                    selector: entry.selector.to_ascii_lowercase(),
+                    public_key,
+                    valid_from: entry.valid_from,
+                    valid_to: entry.valid_to,
+                    pubkey_hash: computed.or(entry.pubkey_hash),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[RegistryEntry] {
+        &self.entries
+    }
+
+    /// Key pinned for `domain`/`selector`, optionally valid at `timestamp`
+    pub fn lookup(
+        &self,
+        domain: &str,
+        selector: &str,
+        timestamp: Option<u64>,
+    ) -> Option<&RegistryEntry> {
+        let domain = domain.trim_end_matches('.');
+        self.entries.iter().find(|entry| {
+            entry.domain.eq_ignore_ascii_case(domain)
+                && entry.selector.eq_ignore_ascii_case(selector)
+                && timestamp.is_none_or(|t| entry.is_valid_at(t))
+        })
+    }
+
+    /// Key for a parsed signature, checked against its `t=` timestamp
+    pub fn lookup_signature(&self, signature: &DkimSignature) -> Option<&RegistryEntry> {
+        self.lookup(&signature.d, &signature.s, signature.t)
+    }
+
+    /// Whether a proof's `pubkey_hash` output belongs to a pinned key
+    pub fn is_allowed_pubkey_hash(&self, pubkey_hash: &str) -> bool {
+        let pubkey_hash = pubkey_hash.trim();
+        self.entries
+            .iter()
+            .any(|entry| entry.pubkey_hash.as_deref() == Some(pubkey_hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa::tests::test_key;
+
+    fn registry_json() -> String {
+        let record = include_str!("../testdata/dkim_test_record.txt").trim();
+        serde_json::json!({
+            "keys": [
+                {
+                    "domain": "Example.com.",
+                    "selector": "test",
+                    "public_key": record,
+                    "valid_from": 1_700_000_000u64,
+                    "valid_to": 1_800_000_000u64
+                },
+                {
+                    "domain": "example.com",
+                    "selector": "test",
+                    "public_key": record,
+                    "valid_from": 1_800_000_001u64
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_lookup_by_domain_selector_and_time() {
+        let registry = KeyRegistry::from_json(&registry_json()).unwrap();
+        let (key, _) = test_key();
+
+        let entry = registry.lookup("EXAMPLE.com", "test", None).unwrap();
+        assert_eq!(entry.public_key, key);
+        assert_eq!(entry.domain, "example.com");
+        assert_eq!(registry.lookup("example.com", "test", Some(1_900_000_000)).unwrap().valid_to, None);
+        assert!(registry.lookup("example.com", "test", Some(1_600_000_000)).is_none());
+        assert!(registry.lookup("example.com", "other", None).is_none());
+
+        let signature = DkimSignature::parse(
+            "v=1; a=rsa-sha256; d=example.com; s=test; t=1750000000; h=from; bh=AA==; b=QQ==",
+        )
+        .unwrap();
+        assert_eq!(registry.lookup_signature(&signature).unwrap().valid_from, 1_700_000_000);
+
+        let expected = field_to_decimal(&pubkey_hash(&key.modulus, 121, 17).unwrap());
+        assert_eq!(entry.pubkey_hash.as_deref(), Some(expected.as_str()));
+        assert!(registry.is_allowed_pubkey_hash(&expected));
+        assert!(!registry.is_allowed_pubkey_hash("42"));
+    }
+
+    /// `pubkey_hash` of Google's ARC key (google.com, s=arc-20240605). Gmail
+    /// ARC-signs the From field of inbound mail even when DMARC fails, and the
+    /// circuit does not check which header field carries the signature, so
+    /// this key must never be accepted.
+    const GOOGLE_ARC_PUBKEY_HASH: &str =
+        "21838080114457976686738784413438640370946884645063666077990237896679109697660";
+
+    #[test]
+    fn test_embedded_registry_rejects_arc_key() {
+        let registry = KeyRegistry::embedded().unwrap();
+        assert!(!registry.is_allowed_pubkey_hash(GOOGLE_ARC_PUBKEY_HASH));
+        assert!(registry.lookup("google.com", "arc-20240605", None).is_none());
+    }
+
+    /// The key that signs BRACU mail verifies the DKIM-Signature of the Gmail
+    /// sample in the repository root
+    #[test]
+    #[ignore = "google._domainkey.g.bracu.ac.bd is not pinned in keys/dkim_registry.json yet"]
+    fn test_embedded_bracu_key() {
+        let registry = KeyRegistry::embedded().unwrap();
+        let email = include_bytes!("../../../Final Exam Schedule, Fall 2025.eml");
+        let message = crate::canonicalization::parse_message(email);
+        let policy = crate::dkim::SignaturePolicy::Domain("g.bracu.ac.bd".to_string());
+        let (index, signature) = crate::dkim::select_signature(&message, &policy).unwrap();
+
+        let entry = registry.lookup_signature(&signature).unwrap();
+        crate::dkim::verify_signature_at(&message, index, &signature, &entry.public_key).unwrap();
+        assert!(registry.is_allowed_pubkey_hash(entry.pubkey_hash.as_deref().unwrap()));
+        assert_ne!(entry.pubkey_hash.as_deref(), Some(GOOGLE_ARC_PUBKEY_HASH));
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        assert!(KeyRegistry::embedded().is_ok());
+        let bad_key = r#"{ "keys": [{ "domain": "x.com", "selector": "s", "public_key": "p=" }] }"#;
+        assert!(KeyRegistry::from_json(bad_key).unwrap_err().contains("s._domainkey.x.com"));
+        assert!(KeyRegistry::from_json("{}").is_err());
+
+        let mut wrong_hash: serde_json::Value = serde_json::from_str(&registry_json()).unwrap();
+        wrong_hash["keys"][0]["pubkey_hash"] = "42".into();
+        let err = KeyRegistry::from_json(&wrong_hash.to_string()).unwrap_err();
+        assert!(err.contains("does not match"), "{}", err);
+    }
+}
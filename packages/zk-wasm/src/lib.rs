@@ -1,21 +1,48 @@
+//! DKIM parsing and verification, circuit input generation, and Groth16
+//! proving and verification for the zkemail circuits.
+//!
+//! The core API is plain Rust and builds for any target. The default `wasm`
+//! feature adds the JavaScript bindings on top; build with
+//! `--no-default-features` to use the crate from native code.
+
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 use ark_bn254::{Bn254, Fr};
-use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{ConstraintMatrices, SynthesisError};
 use ark_serialize::CanonicalDeserialize;
 use ark_snark::SNARK;
-use std::collections::HashMap;
 
-// SHA256 block size in bytes
-const SHA256_BLOCK_SIZE: usize = 64;
+pub mod body_hash;
+pub mod canonicalization;
+pub mod circom;
+pub mod circuit_inputs;
+pub mod dkim;
+pub mod dkim_key;
+pub mod error;
+pub mod key_registry;
+pub mod limbs;
+pub mod nullifier;
+pub mod poseidon;
+pub mod prover;
+pub mod rsa;
+pub mod snarkjs;
+pub mod verifier;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zkey;
+
+use circom::{WitnessCalculator, R1CS};
+use dkim::{DkimSignature, SignaturePolicy};
+use error::ZkWasmError;
+use limbs::LimbConfig;
 
-#[wasm_bindgen(start)]
-pub fn init() {
-    console_error_panic_hook::set_once();
-}
+// SHA256 block size in bytes
+pub(crate) const SHA256_BLOCK_SIZE: usize = 64;
 
 /// Result of partial SHA256 computation for email headers
-#[wasm_bindgen]
-#[derive(Clone)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
 pub struct PartialHashResult {
     /// Intermediate SHA256 state (8 x u32, little-endian)
     state: Vec<u32>,
@@ -27,24 +54,24 @@ pub struct PartialHashResult {
     prehashed_length: u64,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl PartialHashResult {
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn state(&self) -> Vec<u32> {
         self.state.clone()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn remaining(&self) -> Vec<u8> {
         self.remaining.clone()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn total_length(&self) -> u64 {
         self.total_length
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn prehashed_length(&self) -> u64 {
         self.prehashed_length
     }
@@ -64,11 +91,22 @@ impl PartialHashResult {
 ///
 /// # Returns
 /// PartialHashResult with intermediate state and remaining bytes
-#[wasm_bindgen]
+///
+/// `from_header_index`/`from_address_index` of `parse_dkim_from_email` are
+/// offsets into the signed header data, not into `remaining`; use
+/// `precompute_email_header` to get both from the same buffer.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn compute_partial_hash_for_email(
     header_bytes: &[u8],
     max_remaining_len: usize,
-) -> Result<PartialHashResult, JsValue> {
+) -> Result<PartialHashResult, ZkWasmError> {
+    partial_hash(header_bytes, max_remaining_len)
+}
+
+pub(crate) fn partial_hash(
+    header_bytes: &[u8],
+    max_remaining_len: usize,
+) -> Result<PartialHashResult, ZkWasmError> {
     let header_len = header_bytes.len();
 
     // If header fits in circuit, no precomputation needed
@@ -86,15 +124,11 @@ pub fn compute_partial_hash_for_email(
 
     // Find "From:" header position (case-insensitive)
     let from_pos = find_from_header_position(header_bytes)
-        .ok_or_else(|| JsValue::from_str("Could not find 'From:' header in email"))?;
+        .ok_or(ZkWasmError::NoFromHeader)?;
 
     // Calculate split point: must be before From header and at 64-byte boundary
     // Also ensure remaining bytes fit in max_remaining_len
-    let min_split = if header_len > max_remaining_len {
-        header_len - max_remaining_len
-    } else {
-        0
-    };
+    let min_split = header_len.saturating_sub(max_remaining_len);
 
     // Split point must be:
     // 1. At a 64-byte boundary
@@ -104,10 +138,11 @@ pub fn compute_partial_hash_for_email(
 
     if split_point == 0 {
         // Can't precompute, From header is too early
-        // Return initial state with all bytes as remaining
-        return Err(JsValue::from_str(
-            "From header is too close to the start for partial hashing"
-        ));
+        return Err(ZkWasmError::FromHeaderTooEarly {
+            from_index: from_pos,
+            header_length: header_len,
+            max_remaining: max_remaining_len,
+        });
     }
 
     // Compute SHA256 of prefix blocks
@@ -193,7 +228,7 @@ fn calculate_split_point(min_split: usize, from_pos: usize) -> usize {
 /// This computes the intermediate SHA256 state after processing the input.
 /// The input length MUST be a multiple of 64 bytes.
 fn compute_sha256_partial_state(data: &[u8]) -> Vec<u32> {
-    assert!(data.len() % SHA256_BLOCK_SIZE == 0, "Data must be multiple of 64 bytes");
+    assert!(data.len().is_multiple_of(SHA256_BLOCK_SIZE), "Data must be multiple of 64 bytes");
 
     // SHA256 initial state
     let mut state: [u32; 8] = [
@@ -210,6 +245,27 @@ fn compute_sha256_partial_state(data: &[u8]) -> Vec<u32> {
     state.to_vec()
 }
 
+/// SHA256 message padding: `0x80`, zeros, then the bit length (big-endian u64)
+pub fn sha256_pad(data: &[u8]) -> Vec<u8> {
+    let mut padded = data.to_vec();
+    padded.push(0x80);
+    while padded.len() % SHA256_BLOCK_SIZE != SHA256_BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    padded
+}
+
+/// Full SHA256 digest
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    let state = compute_sha256_partial_state(&sha256_pad(data));
+    let mut digest = [0u8; 32];
+    for (out, word) in digest.chunks_mut(4).zip(state) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
 /// Prepare a 64-byte block for SHA256 compression
 fn prepare_block(chunk: &[u8]) -> [u32; 16] {
     let mut block = [0u32; 16];
@@ -296,14 +352,17 @@ fn sha256_compress(state: &mut [u32; 8], block: &[u32; 16]) {
 // ============================================================================
 
 use num_bigint::BigUint;
-use regex::Regex;
 
 /// Parsed DKIM signature data for circuit inputs
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug)]
 pub struct DKIMResult {
-    pubkey_modulus:Vec<String>,
-    pubkey_redc: Vec<String>,
+    /// `None` until the result is built with a public key
+    pubkey_modulus: Option<Vec<String>>,
+    pubkey_redc: Option<Vec<String>>,
     signature: Vec<String>,
+    /// From field and address as offsets into the canonicalized signed
+    /// header data, as located by `circuit_inputs::from_sequences`
     from_header_index: usize,
     from_header_length: usize,
     from_address_index: usize,
@@ -311,181 +370,557 @@ pub struct DKIMResult {
     from_email: String,
     selector: String,
     domain: String,
+    dkim_signature: DkimSignature,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug)]
 pub struct PubkeyResult {
     modulus: Vec<String>,
     redc: Vec<String>,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl PubkeyResult {
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn modulus(&self) -> Vec<String> {
         self.modulus.clone()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn redc(&self) -> Vec<String> {
         self.redc.clone()
     }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl DKIMResult {
-    #[wasm_bindgen(getter)]
-    pub fn pubkey_modulus(&self) -> Vec<String> {
-        self.pubkey_modulus.clone()
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn pubkey_modulus(&self) -> Result<Vec<String>, ZkWasmError> {
+        self.pubkey_modulus.clone().ok_or(ZkWasmError::MissingKey)
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn pubkey_redc(&self) -> Result<Vec<String>, ZkWasmError> {
+        self.pubkey_redc.clone().ok_or(ZkWasmError::MissingKey)
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn pubkey_redc(&self) -> Vec<String> {
-        self.pubkey_redc.clone()
+    /// Whether the modulus and REDC limbs are available
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn has_pubkey(&self) -> bool {
+        self.pubkey_modulus.is_some()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn signature(&self) -> Vec<String> {
         self.signature.clone()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn from_header_index(&self) -> usize {
         self.from_header_index
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn from_header_length(&self) -> usize {
         self.from_header_length
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn from_address_index(&self) -> usize {
         self.from_address_index
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn from_address_length(&self) -> usize {
         self.from_address_length
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn from_email(&self) -> String {
         self.from_email.clone()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn selector(&self) -> String {
         self.selector.clone()
     }
 
-    #[wasm_bindgen(getter)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
     pub fn domain(&self) -> String {
         self.domain.clone()
     }
+
+    /// All tags of the DKIM-Signature the inputs were taken from
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn dkim_signature(&self) -> DkimSignature {
+        self.dkim_signature.clone()
+    }
 }
 
-/// Parse DKIM signature from email and extract circuit inputs
-#[wasm_bindgen]
-pub fn parse_dkim_from_email(email_bytes: &[u8]) -> Result<DKIMResult, JsValue> {
-    let email_str = std::str::from_utf8(email_bytes)
-        .map_err(|e| JsValue::from_str(&format!("Invalid UTF-8: {}", e)))?;
+/// Parse DKIM signature from email and extract the key-independent inputs.
+///
+/// Uses the signature whose `d=` equals `target_domain`, or the one aligned
+/// with the From domain when it is omitted. The result carries no pubkey
+/// limbs: fetch the key for its `selector`/`domain` and call
+/// `parse_dkim_from_email_with_key`.
+///
+/// `config` defaults to the Noir layout for the signature's RSA size.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn parse_dkim_from_email(
+    email_bytes: &[u8],
+    target_domain: Option<String>,
+    config: Option<LimbConfig>,
+) -> Result<DKIMResult, ZkWasmError> {
+    dkim_inputs(email_bytes, None, &signature_policy(target_domain), config)
+}
+
+/// Parse DKIM signature from email and extract every circuit input.
+///
+/// `pubkey` is the DNS TXT record or any other form accepted by
+/// `compute_pubkey_inputs`. The signature must verify under that key, so the
+/// modulus, REDC and signature limbs always belong together.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn parse_dkim_from_email_with_key(
+    email_bytes: &[u8],
+    pubkey: &str,
+    target_domain: Option<String>,
+    config: Option<LimbConfig>,
+) -> Result<DKIMResult, ZkWasmError> {
+    if pubkey.trim().is_empty() {
+        return Err(ZkWasmError::MissingKey);
+    }
+    let key = dkim_key::parse_public_key(pubkey)?;
+    dkim_inputs(email_bytes, Some(&key), &signature_policy(target_domain), config)
+}
+
+fn signature_policy(target_domain: Option<String>) -> SignaturePolicy {
+    match target_domain {
+        Some(domain) => SignaturePolicy::Domain(domain),
+        None => SignaturePolicy::FromAligned,
+    }
+}
+
+/// Build a `DKIMResult`; with a key, the signature is verified first
+fn dkim_inputs(
+    email_bytes: &[u8],
+    key: Option<&rsa::RsaPublicKey>,
+    policy: &SignaturePolicy,
+    config: Option<LimbConfig>,
+) -> Result<DKIMResult, ZkWasmError> {
+    std::str::from_utf8(email_bytes).map_err(|_| ZkWasmError::InvalidUtf8)?;
 
     // Extract DKIM signature
-    let dkim_sig = extract_dkim_signature(email_str)?;
-    
+    let message = canonicalization::parse_message(email_bytes);
+    let (index, dkim_sig) = dkim::select_signature(&message, policy)?;
+    if let Some(key) = key {
+        dkim::verify_signature_at(&message, index, &dkim_sig, key)?;
+    }
+
     // Parse RSA signature from DKIM
     let signature_bigint = parse_base64_to_bigint(&dkim_sig.b)
-        .ok_or_else(|| JsValue::from_str("Failed to parse DKIM signature"))?;
-    
-    // Use stub pubkey relative to parsed signature (Just to fill valid-looking limbs)
-    // The REAL pubkey must be fetched via DNS and processed with compute_pubkey_inputs
-    let pubkey_modulus = create_stub_pubkey();
-    
-    // Convert to 18 limbs (120-bit each for 2048-bit key)
-    let signature_limbs = bigint_to_limbs(&signature_bigint, 18, 120);
-    let pubkey_limbs = bigint_to_limbs(&pubkey_modulus, 18, 120);
-    let pubkey_redc = calculate_redc_param(&pubkey_modulus, 18, 120);
+        .ok_or(ZkWasmError::InvalidBase64 { field: "b" })?;
 
-    // Find From header
-    let (from_index, from_length, addr_index, addr_length, from_email) = 
-        find_from_header_info(email_str)?;
+    // Split into limbs (18 x 120 bits for a 2048-bit key by default)
+    let config = match (config, key) {
+        (Some(config), _) => config,
+        (None, Some(key)) => LimbConfig::noir(limbs::rsa_key_size(&key.modulus)?)?,
+        (None, None) => LimbConfig::noir(limbs::rsa_key_size(&signature_bigint)?)?,
+    };
+    let signature_limbs = config.to_limbs(&signature_bigint)?;
+    let (pubkey_limbs, pubkey_redc) = match key {
+        Some(key) => {
+            config.check_modulus(&key.modulus)?;
+            (
+                Some(config.to_limbs(&key.modulus)?),
+                Some(config.redc_param(&key.modulus)?),
+            )
+        }
+        None => (None, None),
+    };
+
+    // Locate From in the signed header, the same way the circuit inputs do
+    let header = dkim::signed_header_data(&message, index, &dkim_sig);
+    let (from_header, from_address) = circuit_inputs::from_sequences(&header)?;
+    let from_email =
+        String::from_utf8_lossy(&header[from_address.index..from_address.index + from_address.length])
+            .into_owned();
 
     Ok(DKIMResult {
         pubkey_modulus: pubkey_limbs,
         pubkey_redc,
         signature: signature_limbs,
-        from_header_index: from_index,
-        from_header_length: from_length,
-        from_address_index: addr_index,
-        from_address_length: addr_length,
+        from_header_index: from_header.index,
+        from_header_length: from_header.length,
+        from_address_index: from_address.index,
+        from_address_length: from_address.length,
         from_email,
-        selector: dkim_sig.s,
-        domain: dkim_sig.d,
+        selector: dkim_sig.s.clone(),
+        domain: dkim_sig.d.clone(),
+        dkim_signature: dkim_sig,
     })
 }
 
-/// Compute circuit inputs from a real RSA Public Key (base64)
-#[wasm_bindgen]
-pub fn compute_pubkey_inputs(pubkey_base64: &str) -> Result<PubkeyResult, JsValue> {
-    let pubkey_bigint = parse_base64_to_bigint(pubkey_base64)
-        .ok_or_else(|| JsValue::from_str("Failed to parse Public Key base64"))?;
+/// List every well-formed DKIM-Signature of an email, topmost first
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn list_dkim_signatures(email_bytes: &[u8]) -> Vec<DkimSignature> {
+    let message = canonicalization::parse_message(email_bytes);
+    dkim::signature_fields(&message)
+        .into_iter()
+        .filter_map(|(_, parsed)| parsed.ok())
+        .collect()
+}
+
+/// Parse a DKIM-Signature header value (with or without the field name)
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn parse_dkim_signature(header: &str) -> Result<DkimSignature, ZkWasmError> {
+    let value = match header.split_once(':') {
+        Some((name, value)) if name.trim().eq_ignore_ascii_case("DKIM-Signature") => value,
+        _ => header,
+    };
+    DkimSignature::parse(value).map_err(|e| ZkWasmError::InvalidDkimSignature(e.to_string()))
+}
+
+/// Outcome of native DKIM signature verification
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct DkimVerificationResult {
+    valid: bool,
+    step: Option<String>,
+    reason: Option<String>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DkimVerificationResult {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Failing step: "key", "signature", "expiration", "key-algorithm",
+    /// "key-record", "algorithm", "base64", "rsa", "digest-info" or
+    /// "header-hash"; `undefined` when valid
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn step(&self) -> Option<String> {
+        self.step.clone()
+    }
 
-    let modulus = bigint_to_limbs(&pubkey_bigint, 18, 120);
-    let redc = calculate_redc_param(&pubkey_bigint, 18, 120);
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+/// Check an email's DKIM RSA-SHA256 header signature before proving.
+///
+/// `pubkey` takes any form accepted by `compute_pubkey_inputs`. The signature
+/// is chosen as in `parse_dkim_from_email`. Its `a=` must match a TXT
+/// record's `k=` and `h=`, and the record's `s=` and `t=` must allow it.
+/// Given a `verification_time` (unix seconds), a signature whose `x=` is
+/// earlier fails at the "expiration" step.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn verify_dkim_signature(
+    email_bytes: &[u8],
+    pubkey: &str,
+    target_domain: Option<String>,
+    verification_time: Option<u64>,
+) -> DkimVerificationResult {
+    let failed = |step: &str, reason: String| DkimVerificationResult {
+        valid: false,
+        step: Some(step.to_string()),
+        reason: Some(reason),
+    };
+    let record = match dkim_key::parse_key_record(pubkey) {
+        Ok(record) => record,
+        Err(e) => return failed("key", e.to_string()),
+    };
+    let policy = signature_policy(target_domain);
+
+    match dkim::verify_dkim(email_bytes, &record, &policy, verification_time) {
+        Ok(_) => DkimVerificationResult {
+            valid: true,
+            step: None,
+            reason: None,
+        },
+        Err(e) => failed(e.step(), e.to_string()),
+    }
+}
+
+/// Outcome of the body hash (`bh=`) check
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct BodyHashResult {
+    valid: bool,
+    reason: Option<String>,
+    body_hash: Option<String>,
+    body_hash_index: Option<usize>,
+    body_hash_length: Option<usize>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BodyHashResult {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+
+    /// Base64 body hash, when verified
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn body_hash(&self) -> Option<String> {
+        self.body_hash.clone()
+    }
+
+    /// Offset of the `bh=` value in the canonicalized signed header
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn body_hash_index(&self) -> Option<usize> {
+        self.body_hash_index
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn body_hash_length(&self) -> Option<usize> {
+        self.body_hash_length
+    }
+}
+
+/// Check that the (canonicalized, `l=`-limited) body hashes to `bh=`.
+///
+/// The signature is chosen as in `parse_dkim_from_email`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn verify_body_hash(email_bytes: &[u8], target_domain: Option<String>) -> BodyHashResult {
+    let message = canonicalization::parse_message(email_bytes);
+    let policy = signature_policy(target_domain);
+    let checked = dkim::select_signature(&message, &policy)
+        .map_err(|e| e.to_string())
+        .and_then(|(index, signature)| {
+            body_hash::verify_body_hash(&message, index, &signature).map_err(|e| e.to_string())
+        });
+
+    match checked {
+        Ok(body_hash) => BodyHashResult {
+            valid: true,
+            reason: None,
+            body_hash: Some(body_hash.hash),
+            body_hash_index: Some(body_hash.index),
+            body_hash_length: Some(body_hash.length),
+        },
+        Err(reason) => BodyHashResult {
+            valid: false,
+            reason: Some(reason),
+            body_hash: None,
+            body_hash_index: None,
+            body_hash_length: None,
+        },
+    }
+}
+
+/// Compute circuit inputs from a real RSA Public Key.
+///
+/// Accepts the DNS TXT record (`v=DKIM1; k=rsa; p=...`), its base64 `p=`
+/// value (DER SubjectPublicKeyInfo or RSAPublicKey) or a base64 modulus.
+/// `config` defaults to the Noir layout for the key's size.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compute_pubkey_inputs(
+    pubkey_base64: &str,
+    config: Option<LimbConfig>,
+) -> Result<PubkeyResult, ZkWasmError> {
+    let pubkey_bigint = dkim_key::parse_public_key(pubkey_base64)?.modulus;
+    pubkey_inputs(&pubkey_bigint, config)
+}
+
+fn pubkey_inputs(
+    modulus: &BigUint,
+    config: Option<LimbConfig>,
+) -> Result<PubkeyResult, ZkWasmError> {
+    let config = match config {
+        Some(config) => config,
+        None => LimbConfig::noir(limbs::rsa_key_size(modulus)?)?,
+    };
+    config.check_modulus(modulus)?;
 
     Ok(PubkeyResult {
-        modulus,
-        redc
+        modulus: config.to_limbs(modulus)?,
+        redc: config.redc_param(modulus)?,
     })
 }
 
-/// Extract DKIM-Signature header from email
-struct DKIMSignature {
-    b: String,    // base64-encoded signature
-    s: String,   // selector
-    d: String,   // domain
+/// The circuit's `pubkey_hash` (decimal) for a public key split into `k`
+/// limbs of `n` bits, e.g. `n = 121, k = 17` for the circom EmailVerifier
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compute_pubkey_hash(pubkey: &str, n: usize, k: usize) -> Result<String, ZkWasmError> {
+    let key = dkim_key::parse_public_key(pubkey)?;
+    poseidon::pubkey_hash(&key.modulus, n, k)
+        .map(|hash| snarkjs::field_to_decimal(&hash))
+        .map_err(ZkWasmError::LimbLayout)
 }
 
-fn extract_dkim_signature(email: &str) -> Result<DKIMSignature, JsValue> {
-    // Find DKIM-Signature header (can span multiple lines)
-    let dkim_regex = Regex::new(r"(?i)DKIM-Signature:\s*([^\r\n]*(?:\r?\n\s+[^\r\n]*)*)")
-        .map_err(|e| JsValue::from_str(&format!("Regex error: {}", e)))?;
-    
-    let captures = dkim_regex.captures(email)
-        .ok_or_else(|| JsValue::from_str("No DKIM-Signature header found via regex"))?;
-    
-    let dkim_header = captures.get(1)
-        .ok_or_else(|| JsValue::from_str("Failed to extract DKIM header capture"))?
-        .as_str()
-        .replace("\r\n", "")
-        .replace("\n", "");
-
-    // Extract b= (signature)
-    let b_regex = Regex::new(r"b=([A-Za-z0-9+/=\s]+)")
-        .map_err(|e| JsValue::from_str(&format!("Regex error: {}", e)))?;
-    let b = b_regex.captures(&dkim_header)
-        .and_then(|c| c.get(1))
-        .map(|m| m.as_str().to_string())
-        .ok_or_else(|| JsValue::from_str("No signature (b=) found in DKIM header"))?;
-
-    // Extract s= (selector)
-    let s_regex = Regex::new(r"s=([^;\s]+)")
-        .map_err(|e| JsValue::from_str(&format!("Regex error: {}", e)))?;
-    let s = s_regex.captures(&dkim_header)
-        .and_then(|c| c.get(1))
-        .map(|m| m.as_str().to_string())
-        .unwrap_or_default();
-
-    // Extract d= (domain)
-    let d_regex = Regex::new(r"d=([^;\s]+)")
-        .map_err(|e| JsValue::from_str(&format!("Regex error: {}", e)))?;
-    let d = d_regex.captures(&dkim_header)
-        .and_then(|c| c.get(1))
-        .map(|m| m.as_str().to_string())
-        .unwrap_or_default();
-
-    Ok(DKIMSignature { b, s, d })
+/// `EmailNullifier` of `nullifier.circom` for decimal signature limbs (e.g.
+/// `DKIMResult.signature`), the `nullifier` output of `BracuVerifier`.
+///
+/// It is different for each email, so it stops an email from being used
+/// twice but not a student with several emails from the registrar. Do not use
+/// it to allow one review per student; use `derive_review_nullifier`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compute_email_nullifier(signature_limbs: Vec<String>) -> Result<String, ZkWasmError> {
+    nullifier::parse_field_elements(&signature_limbs)
+        .and_then(|limbs| nullifier::email_nullifier(&limbs))
+        .map(|hash| snarkjs::field_to_decimal(&hash))
+        .map_err(ZkWasmError::InvalidInput)
+}
+
+/// `ExternalNullifier` of `nullifier.circom`: the email nullifier scoped to
+/// a faculty and semester, both given as decimal field elements
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compute_external_nullifier(
+    signature_limbs: Vec<String>,
+    faculty_id: &str,
+    semester: &str,
+) -> Result<String, ZkWasmError> {
+    let compute = || {
+        let limbs = nullifier::parse_field_elements(&signature_limbs)?;
+        nullifier::external_nullifier(
+            &limbs,
+            nullifier::parse_field(faculty_id)?,
+            nullifier::parse_field(semester)?,
+        )
+    };
+    compute()
+        .map(|hash| snarkjs::field_to_decimal(&hash))
+        .map_err(ZkWasmError::InvalidInput)
+}
+
+/// `AddressNullifier` of `nullifier.circom`: a student's email nullifier,
+/// from their signed From address and their decimal `secret`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn compute_address_nullifier(from_address: &str, secret: &str) -> Result<String, ZkWasmError> {
+    nullifier::parse_field(secret)
+        .and_then(|secret| nullifier::address_nullifier(from_address.as_bytes(), secret))
+        .map(|hash| snarkjs::field_to_decimal(&hash))
+        .map_err(ZkWasmError::InvalidInput)
+}
+
+/// A fresh random `secret` for `compute_address_nullifier` (decimal). The
+/// student keeps it and uses it for every review; it is never sent anywhere.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn generate_nullifier_secret() -> String {
+    snarkjs::field_to_decimal(&nullifier::random_secret())
+}
+
+/// The `review_nullifier` a `BracuReviewVerifier` proof outputs for a
+/// student's `email_nullifier` (from `compute_address_nullifier`): one value
+/// per student, faculty and semester
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn derive_review_nullifier(
+    email_nullifier: &str,
+    faculty_initials: &str,
+    semester: &str,
+) -> Result<String, ZkWasmError> {
+    nullifier::parse_field(email_nullifier)
+        .and_then(|email_nullifier| {
+            nullifier::review_nullifier(email_nullifier, faculty_initials, semester)
+        })
+        .map(|hash| snarkjs::field_to_decimal(&hash))
+        .map_err(ZkWasmError::InvalidInput)
+}
+
+/// Parse a DKIM key record as published in DNS TXT
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn parse_dkim_key_record(txt: &str) -> Result<dkim_key::DkimKeyRecord, ZkWasmError> {
+    Ok(dkim_key::DkimKeyRecord::parse(txt)?)
+}
+
+/// Inputs for the circom `BracuVerifier` as a JSON object string:
+/// `in_padded`, `pubkey`, `signature` and `in_len_padded_bytes`.
+///
+/// `max_header_bytes` is the circuit's header size (1024 for
+/// `bracu_verifier.circom`). The signature must verify under `pubkey`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn generate_circom_inputs(
+    email_bytes: &[u8],
+    pubkey: &str,
+    max_header_bytes: usize,
+    target_domain: Option<String>,
+) -> Result<String, ZkWasmError> {
+    let key = dkim_key::parse_public_key(pubkey)?;
+    circuit_inputs::circom_inputs(
+        email_bytes,
+        &key,
+        &signature_policy(target_domain),
+        max_header_bytes,
+    )
+    .map(|inputs| inputs.to_json().to_string())
+}
+
+/// Inputs for the circom `BracuReviewVerifier` as a JSON object string: those
+/// of `generate_circom_inputs` plus `from_address_index`, `secret`, `faculty`
+/// and `semester`. `secret` is the student's decimal nullifier secret; the
+/// proof's `review_nullifier` output equals `derive_review_nullifier` of
+/// their `compute_address_nullifier`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn generate_circom_review_inputs(
+    email_bytes: &[u8],
+    pubkey: &str,
+    max_header_bytes: usize,
+    secret: &str,
+    faculty_initials: &str,
+    semester: &str,
+    target_domain: Option<String>,
+) -> Result<String, ZkWasmError> {
+    let key = dkim_key::parse_public_key(pubkey)?;
+    let secret = nullifier::parse_field(secret).map_err(ZkWasmError::InvalidInput)?;
+    circuit_inputs::circom_review_inputs(
+        email_bytes,
+        &key,
+        &signature_policy(target_domain),
+        max_header_bytes,
+        secret,
+        faculty_initials,
+        semester,
+    )
+    .map(|inputs| inputs.to_json().to_string())
+}
+
+/// Inputs for the Noir `bracu_verifier`, ready to serialize with
+/// `to_json()` (noir_js) or `to_prover_toml()` (nargo).
+///
+/// `max_remaining_len` is the circuit's `remaining_header` size; headers
+/// before From are precomputed when the signed header is longer. The
+/// signature must verify under `pubkey`.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn generate_noir_inputs(
+    email_bytes: &[u8],
+    pubkey: &str,
+    max_remaining_len: usize,
+    target_domain: Option<String>,
+) -> Result<circuit_inputs::NoirInputs, ZkWasmError> {
+    let key = dkim_key::parse_public_key(pubkey)?;
+    circuit_inputs::noir_inputs(
+        email_bytes,
+        &key,
+        &signature_policy(target_domain),
+        max_remaining_len,
+    )
+}
+
+/// Partial SHA-256 of the canonicalized signed header (the bytes DKIM
+/// actually hashes) with the From header and address indices relative to
+/// `partial_hash.remaining`, the buffer the circuit sees.
+///
+/// The From header always lies entirely inside that buffer.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn precompute_email_header(
+    email_bytes: &[u8],
+    max_remaining_len: usize,
+    target_domain: Option<String>,
+) -> Result<circuit_inputs::PrecomputedHeader, ZkWasmError> {
+    let message = canonicalization::parse_message(email_bytes);
+    let (index, signature) = dkim::select_signature(&message, &signature_policy(target_domain))?;
+    let header = dkim::signed_header_data(&message, index, &signature);
+    circuit_inputs::precompute_header(&header, max_remaining_len)
 }
 
 /// Parse base64 to BigInt
@@ -497,208 +932,460 @@ fn parse_base64_to_bigint(b64: &str) -> Option<BigUint> {
     Some(BigUint::from_bytes_be(&bytes))
 }
 
-/// Create stub 2048-bit RSA public key (in production, fetch from DNS)
-fn create_stub_pubkey() -> BigUint {
-    // This is a mock value - in production you'd fetch the actual public key from DNS
-    // For Gmail's DKIM, you'd query: {selector}._domainkey.gmail.com TXT
-    BigUint::from(65537u32) // Just e=65537 as placeholder
-}
-
-/// Convert BigInt to limbs (little-endian)
-fn bigint_to_limbs(value: &BigUint, num_limbs: usize, limb_bits: usize) -> Vec<String> {
-    let mut limbs = vec![];
-    let limb_mask = (BigUint::from(1u32) << limb_bits) - 1u32;
-    let mut temp = value.clone();
 
-    for _ in 0..num_limbs {
-        let limb = &temp & &limb_mask;
-        limbs.push(limb.to_string());
-        temp >>= limb_bits;
-    }
+// ============================================================================
+// Pinned DKIM key registry
+// ============================================================================
 
-    limbs
+/// Allowlist of pinned DKIM keys for browser and server checks
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct DkimKeyRegistry {
+    registry: key_registry::KeyRegistry,
 }
 
-/// Calculate REDC parameter for Montgomery/Barrett reduction used in Noir
-fn calculate_redc_param(modulus: &BigUint, num_limbs: usize, limb_bits: usize) -> Vec<String> {
-    // Noir's BigNum library uses Barrett Reduction.
-    // The parameter is: floor(2^(2*k + 4) / modulus)
-    // where k is the bit size defined in the circuit's BigNumParams (2048).
-    // It must strictly be 2048, not modulus.bits() (which might be 2047) nor container bits (2160).
-
-    let k = 2048u64;
-    // 4 bits for overflow protection as defined in noir-bignum-paramgen
-    let overflow_bits = 4;
-    
-    let one = BigUint::from(1u32);
-    let shift_amount = (k as u64) * 2 + overflow_bits;
-    let multiplicand = one << shift_amount;
-    
-    let redc_param = multiplicand / modulus;
-
-    // Split result into the requested number of limbs
-    bigint_to_limbs(&redc_param, num_limbs, limb_bits)
-}
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DkimKeyRegistry {
+    /// The registry embedded in this build (`keys/dkim_registry.json`); empty
+    /// until keys are pinned there, so `lookup` returns `undefined` for every
+    /// domain until then
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Result<DkimKeyRegistry, ZkWasmError> {
+        let registry =
+            key_registry::KeyRegistry::embedded().map_err(ZkWasmError::InvalidRegistry)?;
+        Ok(DkimKeyRegistry { registry })
+    }
 
-/// Find From header and email address positions
-fn find_from_header_info(email: &str) -> Result<(usize, usize, usize, usize, String), JsValue> {
-    // Find From header
-    let from_regex = Regex::new(r"(?im)^From:\s*([^\r\n]+)")
-        .map_err(|e| JsValue::from_str(&format!("Regex error: {}", e)))?;
-    
-    let captures = from_regex.captures(email)
-        .ok_or_else(|| JsValue::from_str("No From header found"))?;
-    
-    let from_match = captures.get(0)
-        .ok_or_else(|| JsValue::from_str("Failed to extract From header"))?;
-    
-    let from_index = from_match.start();
-    let from_length = from_match.end() - from_match.start();
+    /// A caller-supplied registry in the same JSON format
+    pub fn from_json(json: &str) -> Result<DkimKeyRegistry, ZkWasmError> {
+        let registry =
+            key_registry::KeyRegistry::from_json(json).map_err(ZkWasmError::InvalidRegistry)?;
+        Ok(DkimKeyRegistry { registry })
+    }
 
-    // Extract email address from From header
-    let from_content = captures.get(1)
-        .ok_or_else(|| JsValue::from_str("Failed to extract From content"))?
-        .as_str();
-    
-    let email_regex = Regex::new(r"<([^>]+)>|([a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,})")
-        .map_err(|e| JsValue::from_str(&format!("Regex error: {}", e)))?;
-    
-    let email_captures = email_regex.captures(from_content)
-        .ok_or_else(|| JsValue::from_str("No email address found in From header"))?;
-    
-    let from_email = email_captures.get(1)
-        .or_else(|| email_captures.get(2))
-        .ok_or_else(|| JsValue::from_str("Failed to extract email address"))?
-        .as_str()
-        .to_lowercase();
+    /// Base64 modulus pinned for `domain`/`selector`, ready for
+    /// `parse_dkim_from_email_with_key`; `undefined` when not pinned
+    pub fn lookup(&self, domain: &str, selector: &str, timestamp: Option<u64>) -> Option<String> {
+        use base64::{engine::general_purpose, Engine as _};
+        self.registry
+            .lookup(domain, selector, timestamp)
+            .map(|entry| general_purpose::STANDARD.encode(entry.public_key.modulus.to_bytes_be()))
+    }
 
-    // Find email address position in full email
-    let addr_index = email.find(&from_email)
-        .ok_or_else(|| JsValue::from_str("Could not find email address in email"))?;
-    let addr_length = from_email.len();
+    /// Whether a proof's `pubkey_hash` belongs to a pinned key
+    pub fn is_allowed_pubkey_hash(&self, pubkey_hash: &str) -> bool {
+        self.registry.is_allowed_pubkey_hash(pubkey_hash)
+    }
 
-    Ok((from_index, from_length, addr_index, addr_length, from_email))
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn size(&self) -> usize {
+        self.registry.entries().len()
+    }
 }
 
-
 // ============================================================================
 // Existing ZK Verifier/Prover code below
 // ============================================================================
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct ZKWASMVerifier {
-    verifying_key: Option<VerifyingKey<Bn254>>,
+    /// Verifying key with the pairing precomputation done once at load time
+    prepared_key: Option<PreparedVerifyingKey<Bn254>>,
+}
+
+impl Default for ZKWASMVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl ZKWASMVerifier {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> Self {
         Self {
-            verifying_key: None,
+            prepared_key: None,
         }
     }
 
-    #[wasm_bindgen]
-    pub fn load_verifying_key(&mut self, vk_bytes: &[u8]) -> Result<(), JsValue> {
-        let vk = VerifyingKey::<Bn254>::deserialize_compressed(vk_bytes)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize verifying key: {:?}", e)))?;
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn load_verifying_key(&mut self, vk_bytes: &[u8]) -> Result<(), ZkWasmError> {
+        let vk = deserialize_verifying_key(vk_bytes)?;
         
-        self.verifying_key = Some(vk);
+        self.set_verifying_key(&vk);
         Ok(())
     }
 
-    #[wasm_bindgen]
-    pub fn verify_proof(&self, proof_bytes: &[u8], public_inputs: &[u8]) -> Result<bool, JsValue> {
-        let vk = self.verifying_key.as_ref()
-            .ok_or_else(|| JsValue::from_str("Verifying key not loaded"))?;
+    /// Load a snarkjs `verification_key.json` (decimal-string coordinates)
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn load_verifying_key_json(&mut self, vk_json: &str) -> Result<(), ZkWasmError> {
+        let vk = snarkjs::verifying_key_from_json(vk_json)
+            .map_err(ZkWasmError::MalformedVerifyingKey)?;
 
-        let proof = Proof::<Bn254>::deserialize_compressed(proof_bytes)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize proof: {:?}", e)))?;
+        self.set_verifying_key(&vk);
+        Ok(())
+    }
 
-        let public_inputs_vec: Vec<Fr> = public_inputs
-            .chunks(32)
-            .map(|chunk| {
-                Fr::deserialize_compressed(chunk)
-                    .map_err(|e| JsValue::from_str(&format!("Failed to deserialize public input: {:?}", e)))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn verify_proof(&self, proof_bytes: &[u8], public_inputs: &[u8]) -> Result<bool, ZkWasmError> {
+        let pvk = self.prepared_key.as_ref()
+            .ok_or(ZkWasmError::KeyNotLoaded)?;
+
+        let proof = deserialize_proof(proof_bytes)?;
+        let public_inputs_vec = deserialize_public_inputs(public_inputs)?;
+        if public_inputs_vec.len() + 1 != pvk.vk.gamma_abc_g1.len() {
+            return Err(ZkWasmError::PublicSignalCount {
+                expected: pvk.vk.gamma_abc_g1.len() - 1,
+                actual: public_inputs_vec.len(),
+            });
+        }
 
-        let result = Groth16::<Bn254>::verify(vk, &public_inputs_vec, &proof)
-            .map_err(|e| JsValue::from_str(&format!("Verification failed: {:?}", e)))?;
+        let result = Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs_vec, &proof)
+            .map_err(groth16_error)?;
 
         Ok(result)
     }
 }
 
-#[wasm_bindgen]
+impl ZKWASMVerifier {
+    fn set_verifying_key(&mut self, vk: &VerifyingKey<Bn254>) {
+        self.prepared_key = Some(ark_groth16::prepare_verifying_key(vk));
+    }
+
+    /// Verify a snarkjs `{ pi_a, pi_b, pi_c }` proof against decimal `publicSignals`
+    pub fn verify_snarkjs(
+        &self,
+        proof: &serde_json::Value,
+        public_signals: &serde_json::Value,
+    ) -> Result<bool, ZkWasmError> {
+        let pvk = self.prepared_key.as_ref()
+            .ok_or(ZkWasmError::KeyNotLoaded)?;
+        let proof = snarkjs::proof_from_json(proof).map_err(ZkWasmError::MalformedProof)?;
+        let public_signals = snarkjs::public_signals_from_json(public_signals)
+            .map_err(ZkWasmError::MalformedPublicSignals)?;
+
+        if public_signals.len() + 1 != pvk.vk.gamma_abc_g1.len() {
+            return Err(ZkWasmError::PublicSignalCount {
+                expected: pvk.vk.gamma_abc_g1.len() - 1,
+                actual: public_signals.len(),
+            });
+        }
+
+        Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_signals, &proof)
+            .map_err(groth16_error)
+    }
+
+    /// Verify many snarkjs proofs at once with a single multi-pairing.
+    ///
+    /// Returns `false` if any proof is invalid; verify individually to find which.
+    pub fn verify_snarkjs_batch(
+        &self,
+        proofs: &[serde_json::Value],
+        public_signals: &[serde_json::Value],
+    ) -> Result<bool, ZkWasmError> {
+        let pvk = self.prepared_key.as_ref()
+            .ok_or(ZkWasmError::KeyNotLoaded)?;
+        if proofs.len() != public_signals.len() {
+            return Err(ZkWasmError::InvalidInput(format!(
+                "Got {} proofs but {} public signal arrays",
+                proofs.len(),
+                public_signals.len()
+            )));
+        }
+
+        let batch = proofs
+            .iter()
+            .zip(public_signals)
+            .enumerate()
+            .map(|(i, (proof, signals))| {
+                let proof = snarkjs::proof_from_json(proof)
+                    .map_err(|e| ZkWasmError::MalformedProof(format!("Proof {}: {}", i, e)))?;
+                let signals = snarkjs::public_signals_from_json(signals)
+                    .map_err(|e| ZkWasmError::MalformedPublicSignals(format!("Proof {}: {}", i, e)))?;
+                Ok((proof, signals))
+            })
+            .collect::<Result<Vec<_>, ZkWasmError>>()?;
+
+        verifier::verify_batch(pvk, &batch)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub struct ZKWASMProver {
     proving_key: Option<ProvingKey<Bn254>>,
+    /// Constraint matrices shipped with a snarkjs `.zkey`; `None` for arkworks keys
+    zkey_matrices: Option<ConstraintMatrices<Fr>>,
+    circuit: Option<LoadedCircuit>,
+}
+
+/// Circom circuit loaded into the prover
+struct LoadedCircuit {
+    r1cs: R1CS,
+    witness_calculator: WitnessCalculator,
+}
+
+impl Default for ZKWASMProver {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl ZKWASMProver {
-    #[wasm_bindgen(constructor)]
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
     pub fn new() -> Self {
         Self { 
             proving_key: None,
+            zkey_matrices: None,
+            circuit: None,
         }
     }
 
-    #[wasm_bindgen]
-    pub fn load_circuit(&mut self, _wasm_bytes: &[u8], _r1cs_bytes: &[u8]) -> Result<(), JsValue> {
-        Ok(())
+    /// Load the circom witness generator (`.wasm`) and constraint system (`.r1cs`)
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn load_circuit(&mut self, wasm_bytes: &[u8], r1cs_bytes: &[u8]) -> Result<(), ZkWasmError> {
+        self.load_circuit_bytes(wasm_bytes, r1cs_bytes)
+            .map_err(ZkWasmError::MalformedCircuit)
     }
 
-    #[wasm_bindgen]
-    pub fn load_proving_key(&mut self, pk_bytes: &[u8]) -> Result<(), JsValue> {
+    /// Load a proving key: either a snarkjs `.zkey` file or compressed arkworks bytes
+    #[cfg_attr(feature = "wasm", wasm_bindgen)]
+    pub fn load_proving_key(&mut self, pk_bytes: &[u8]) -> Result<(), ZkWasmError> {
+        if zkey::is_zkey(pk_bytes) {
+            let (pk, matrices) = zkey::read_zkey(pk_bytes)
+                .map_err(|e| ZkWasmError::MalformedProvingKey(format!("zkey: {}", e)))?;
+            self.proving_key = Some(pk);
+            self.zkey_matrices = Some(matrices);
+            return Ok(());
+        }
+
         let pk = ProvingKey::<Bn254>::deserialize_compressed(pk_bytes)
-            .map_err(|e| JsValue::from_str(&format!("Failed to deserialize proving key: {:?}", e)))?;
+            .map_err(|e| ZkWasmError::MalformedProvingKey(e.to_string()))?;
         
         self.proving_key = Some(pk);
+        self.zkey_matrices = None;
         Ok(())
     }
+}
 
-    #[wasm_bindgen]
-    pub fn generate_proof(&self, inputs: JsValue) -> Result<JsValue, JsValue> {
-        let _inputs_map: HashMap<String, Vec<String>> = 
-            serde_wasm_bindgen::from_value(inputs)
-                .map_err(|e| JsValue::from_str(&format!("Failed to parse inputs: {:?}", e)))?;
-        
-        let mock_proof = serde_json::json!({
-            "proof": {
-                "pi_a": ["0x123456789", "0x987654321", "0x1"],
-                "pi_b": [["0x123456789", "0x987654321"], ["0x123456789", "0x987654321"], ["0x1", "0x1"]],
-                "pi_c": ["0x123456789", "0x987654321", "0x1"],
-                "protocol": "groth16",
-                "curve": "bn128"
-            },
-            "publicSignals": ["0x0", "0x1", "0x2", "0x3", "0x4"]
+impl ZKWASMProver {
+    fn load_circuit_bytes(&mut self, wasm_bytes: &[u8], r1cs_bytes: &[u8]) -> Result<(), String> {
+        let r1cs = circom::parse_r1cs(r1cs_bytes)?;
+        let witness_calculator = WitnessCalculator::new(wasm_bytes)?;
+        if witness_calculator.witness_size() != r1cs.num_wires {
+            return Err(format!(
+                "Witness generator produces {} values but the R1CS has {} wires",
+                witness_calculator.witness_size(),
+                r1cs.num_wires
+            ));
+        }
+
+        self.circuit = Some(LoadedCircuit {
+            r1cs,
+            witness_calculator,
         });
-        
-        Ok(JsValue::from_str(&mock_proof.to_string()))
+        Ok(())
+    }
+
+    /// Witness calculation and Groth16 proving, returning the snarkjs
+    /// `{ proof, publicSignals }` object
+    pub fn prove_inputs(&mut self, inputs: &serde_json::Value) -> Result<serde_json::Value, ZkWasmError> {
+        let pk = self.proving_key.as_ref()
+            .ok_or(ZkWasmError::ProvingKeyNotLoaded)?;
+        let circuit = self.circuit.as_mut()
+            .ok_or(ZkWasmError::CircuitNotLoaded)?;
+
+        let witness = circuit.witness_calculator.calculate_witness(inputs)
+            .map_err(ZkWasmError::Witness)?;
+        circuit.r1cs.check_witness(&witness).map_err(ZkWasmError::Witness)?;
+        let proof = match &self.zkey_matrices {
+            Some(matrices) => {
+                if matrices.num_instance_variables != circuit.r1cs.num_instance() {
+                    return Err(ZkWasmError::MalformedProvingKey(
+                        "zkey does not match the loaded circuit's public signals".to_string(),
+                    ));
+                }
+                prover::prove_with_matrices(pk, matrices, &witness)
+            }
+            None => prover::prove(pk, &circuit.r1cs, &witness),
+        }
+        .map_err(ZkWasmError::Proving)?;
+        let public_signals = prover::public_signals(&circuit.r1cs, &witness);
+
+        Ok(prover::proof_to_snarkjs_json(&proof, &public_signals))
     }
 }
 
-#[wasm_bindgen]
+/// Outcome of a standalone proof verification
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct VerificationResult {
+    valid: bool,
+    reason: Option<String>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl VerificationResult {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Why verification failed; `undefined` for valid proofs
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn reason(&self) -> Option<String> {
+        self.reason.clone()
+    }
+}
+
+/// Verify a Groth16 proof without keeping a verifier around.
+///
+/// Each argument may be snarkjs JSON (`verification_key.json`, the
+/// `{ pi_a, pi_b, pi_c }` proof and the `publicSignals` array) or compressed
+/// arkworks bytes (public signals as consecutive 32-byte `Fr` values).
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn verify_proof(
-    _vk_bytes: &[u8], 
-    _proof_bytes: &[u8], 
+    vk_bytes: &[u8], 
+    proof_bytes: &[u8], 
+    public_signals: &[u8]
+) -> VerificationResult {
+    match verify_proof_bytes(vk_bytes, proof_bytes, public_signals) {
+        Ok(true) => VerificationResult { valid: true, reason: None },
+        Ok(false) => VerificationResult {
+            valid: false,
+            reason: Some("Proof does not verify against the public signals".to_string()),
+        },
+        Err(e) => VerificationResult { valid: false, reason: Some(e.to_string()) },
+    }
+}
+
+/// Always-true verifier for local development against mock proofs.
+///
+/// Only compiled with the `mock-verifier` feature, so production builds cannot
+/// accept unverified proofs by accident.
+#[cfg(feature = "mock-verifier")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn mock_verify_proof(
+    _vk_bytes: &[u8],
+    _proof_bytes: &[u8],
     _public_signals: &[u8]
 ) -> bool {
     true
 }
 
-#[wasm_bindgen]
-pub fn generate_proof(
-    _r1cs_bytes: &[u8],
-    _wasm_bytes: &[u8],
-    _zkey_bytes: &[u8],
-    _inputs: JsValue
-) -> Result<JsValue, JsValue> {
-    let mock_proof = r#"{"proof":{"pi_a":["0x123456789","0x987654321","0x1"],"pi_b":[["0x123456789","0x987654321"],["0x123456789","0x987654321"],["0x1","0x1"]],"pi_c":["0x123456789","0x987654321","0x1"],"protocol":"groth16","curve":"bn128"},"publicSignals":["0x0","0x1","0x2","0x3","0x4"]}"#;
-    
-    Ok(JsValue::from_str(mock_proof))
+fn verify_proof_bytes(
+    vk_bytes: &[u8],
+    proof_bytes: &[u8],
+    public_signals: &[u8],
+) -> Result<bool, ZkWasmError> {
+    let vk = decode_json_or_compressed(
+        vk_bytes,
+        snarkjs::verifying_key_from_json,
+        deserialize_verifying_key,
+        ZkWasmError::MalformedVerifyingKey,
+    )?;
+
+    let proof = decode_json_or_compressed(
+        proof_bytes,
+        |text| {
+            serde_json::from_str(text)
+                .map_err(|e| format!("Invalid proof JSON: {}", e))
+                .and_then(|json| snarkjs::proof_from_json(&json))
+        },
+        deserialize_proof,
+        ZkWasmError::MalformedProof,
+    )?;
+
+    let public_signals = decode_json_or_compressed(
+        public_signals,
+        |text| {
+            serde_json::from_str(text)
+                .map_err(|e| format!("Invalid public signals JSON: {}", e))
+                .and_then(|json| snarkjs::public_signals_from_json(&json))
+        },
+        deserialize_public_inputs,
+        ZkWasmError::MalformedPublicSignals,
+    )?;
+
+    if public_signals.len() + 1 != vk.gamma_abc_g1.len() {
+        return Err(ZkWasmError::PublicSignalCount {
+            expected: vk.gamma_abc_g1.len() - 1,
+            actual: public_signals.len(),
+        });
+    }
+
+    Groth16::<Bn254>::verify(&vk, &public_signals, &proof).map_err(groth16_error)
+}
+
+/// arkworks fails a Groth16 check only when the key's IC does not fit the
+/// inputs or the pairing is degenerate; neither is a bad public signal
+fn groth16_error(e: SynthesisError) -> ZkWasmError {
+    match e {
+        SynthesisError::MalformedVerifyingKey => ZkWasmError::MalformedVerifyingKey(e.to_string()),
+        e => ZkWasmError::Verification(e.to_string()),
+    }
+}
+
+/// Decode snarkjs JSON, falling back to the compressed arkworks encoding.
+///
+/// The format follows from what parses, not from the first byte: raw public
+/// inputs whose first signal is 91 (`[`) or 123 (`{`) are valid UTF-8 too.
+/// When neither parses, input that looks like JSON gets the JSON error.
+fn decode_json_or_compressed<T>(
+    bytes: &[u8],
+    from_json: impl FnOnce(&str) -> Result<T, String>,
+    from_compressed: impl FnOnce(&[u8]) -> Result<T, ZkWasmError>,
+    malformed: fn(String) -> ZkWasmError,
+) -> Result<T, ZkWasmError> {
+    let json_error = match std::str::from_utf8(bytes) {
+        Ok(text) => match from_json(text) {
+            Ok(value) => return Ok(value),
+            Err(e) => Some(e),
+        },
+        Err(_) => None,
+    };
+    let looks_like_json = matches!(
+        bytes.iter().find(|b| !b.is_ascii_whitespace()),
+        Some(b'{') | Some(b'[')
+    );
+    from_compressed(bytes).map_err(|e| match json_error {
+        Some(json_error) if looks_like_json => malformed(json_error),
+        _ => e,
+    })
+}
+
+/// A compressed arkworks verifying key with at least the constant `IC` point,
+/// as `snarkjs::verifying_key_from_json` requires
+fn deserialize_verifying_key(vk_bytes: &[u8]) -> Result<VerifyingKey<Bn254>, ZkWasmError> {
+    let vk = VerifyingKey::<Bn254>::deserialize_compressed(vk_bytes)
+        .map_err(|e| ZkWasmError::MalformedVerifyingKey(e.to_string()))?;
+    if vk.gamma_abc_g1.is_empty() {
+        return Err(ZkWasmError::MalformedVerifyingKey(
+            "gamma_abc_g1 (IC) must contain at least one point".to_string(),
+        ));
+    }
+    Ok(vk)
+}
+
+fn deserialize_proof(proof_bytes: &[u8]) -> Result<Proof<Bn254>, ZkWasmError> {
+    Proof::<Bn254>::deserialize_compressed(proof_bytes)
+        .map_err(|e| ZkWasmError::MalformedProof(e.to_string()))
+}
+
+/// Public inputs as consecutive 32-byte compressed `Fr` values
+fn deserialize_public_inputs(public_inputs: &[u8]) -> Result<Vec<Fr>, ZkWasmError> {
+    if !public_inputs.len().is_multiple_of(32) {
+        return Err(ZkWasmError::MalformedPublicSignals(format!(
+            "Public inputs must be a multiple of 32 bytes, got {}",
+            public_inputs.len()
+        )));
+    }
+    public_inputs
+        .chunks(32)
+        .map(|chunk| {
+            Fr::deserialize_compressed(chunk)
+                .map_err(|e| ZkWasmError::MalformedPublicSignals(e.to_string()))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -718,6 +1405,176 @@ mod tests {
         assert!(state.iter().any(|&x| x != 0));
     }
 
+    #[test]
+    fn test_sha256_digest() {
+        let digest: String = sha256(b"abc").iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(digest, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(sha256_pad(&[0u8; 55]).len(), 64);
+        assert_eq!(sha256_pad(&[0u8; 56]).len(), 128);
+    }
+
+    #[test]
+    fn test_dkim_inputs_require_a_matching_key() {
+        let (key, _) = rsa::tests::test_key();
+        let email = dkim::tests::sign_email(dkim::tests::TEST_EMAIL);
+        let policy = SignaturePolicy::FromAligned;
+
+        let result = dkim_inputs(email.as_bytes(), Some(&key), &policy, None).unwrap();
+        assert_eq!(result.pubkey_modulus, Some(LimbConfig::NOIR_2048.to_limbs(&key.modulus).unwrap()));
+        assert_eq!(result.pubkey_redc.as_ref().map(Vec::len), Some(18));
+        assert_eq!(result.from_email, "alice@example.com");
+
+        // Without a key there are no stub limbs
+        let result = dkim_inputs(email.as_bytes(), None, &policy, None).unwrap();
+        assert!(result.pubkey_modulus.is_none() && result.pubkey_redc.is_none());
+        assert_eq!(result.selector, "test");
+
+        let wrong_key = rsa::RsaPublicKey::from_modulus(&key.modulus - 2u32);
+        let err = dkim_inputs(email.as_bytes(), Some(&wrong_key), &policy, None).unwrap_err();
+        assert_eq!(err.code(), "DKIM_VERIFICATION_FAILED");
+        assert!(err.to_string().contains("does not verify"), "{}", err);
+    }
+
+    #[test]
+    fn test_verify_dkim_signature_checks_the_key_record() {
+        let record = include_str!("../testdata/dkim_test_record.txt").trim();
+        let email = dkim::tests::sign_email(dkim::tests::TEST_EMAIL);
+
+        let result = verify_dkim_signature(email.as_bytes(), record, None, None);
+        assert!(result.valid(), "{:?}", result.reason());
+
+        for (tags, step) in [
+            ("h=sha1; p=", "key-algorithm"),
+            ("h=sha1:sha512; p=", "key-algorithm"),
+            ("s=tlsrpt; p=", "key-record"),
+            ("t=y; p=", "key-record"),
+        ] {
+            let restricted = record.replacen("p=", tags, 1);
+            let result = verify_dkim_signature(email.as_bytes(), &restricted, None, None);
+            assert!(!result.valid());
+            assert_eq!(result.step().as_deref(), Some(step), "{}", tags);
+        }
+        let sha256_only = record.replacen("p=", "h=SHA256; p=", 1);
+        let result = verify_dkim_signature(email.as_bytes(), &sha256_only, None, None);
+        assert!(result.valid(), "{:?}", result.reason());
+    }
+
+    #[test]
+    fn test_verify_dkim_signature_checks_expiration() {
+        let record = include_str!("../testdata/dkim_test_record.txt").trim();
+        let email = dkim::tests::sign_email(
+            &dkim::tests::TEST_EMAIL.replace("s=test;", "s=test; t=1700000000; x=1700086400;"),
+        );
+        let verify_at = |now| verify_dkim_signature(email.as_bytes(), record, None, now);
+
+        assert!(verify_at(None).valid());
+        assert!(verify_at(Some(1_700_000_000)).valid());
+        let result = verify_at(Some(1_800_000_000));
+        assert!(!result.valid());
+        assert_eq!(result.step().as_deref(), Some("expiration"));
+    }
+
+    #[test]
+    fn test_dkim_inputs_locate_the_signed_from() {
+        // The address also appears earlier, and not in lowercase
+        let email = dkim::tests::TEST_EMAIL
+            .replace("Received: by mail.example.com", "Return-Path: <Alice@Example.com>")
+            .replace("alice@example.com", "Alice@Example.com");
+        let policy = SignaturePolicy::FromAligned;
+        let result = dkim_inputs(email.as_bytes(), None, &policy, None).unwrap();
+        assert_eq!(result.from_email, "Alice@Example.com");
+
+        let message = canonicalization::parse_message(email.as_bytes());
+        let (index, signature) = dkim::select_signature(&message, &policy).unwrap();
+        let header = dkim::signed_header_data(&message, index, &signature);
+        let span = |index: usize, length: usize| &header[index..index + length];
+        assert_eq!(
+            span(result.from_header_index, result.from_header_length),
+            b"from:Alice <Alice@Example.com>"
+        );
+        assert_eq!(
+            span(result.from_address_index, result.from_address_length),
+            b"Alice@Example.com"
+        );
+    }
+
+    #[test]
+    fn test_limb_config_is_threaded_through() {
+        let (key, _) = rsa::tests::test_key();
+        let email = dkim::tests::sign_email(dkim::tests::TEST_EMAIL);
+        let policy = SignaturePolicy::FromAligned;
+        let circom = LimbConfig::CIRCOM_2048;
+
+        let result = dkim_inputs(email.as_bytes(), Some(&key), &policy, Some(circom)).unwrap();
+        assert_eq!(result.signature.len(), 17);
+        assert_eq!(result.pubkey_modulus, Some(circom.to_limbs(&key.modulus).unwrap()));
+        assert_eq!(result.pubkey_redc, Some(circom.redc_param(&key.modulus).unwrap()));
+
+        let pubkey = pubkey_inputs(&key.modulus, Some(circom)).unwrap();
+        assert_eq!(pubkey.modulus, result.pubkey_modulus.unwrap());
+        assert_eq!(pubkey_inputs(&key.modulus, None).unwrap().modulus.len(), 18);
+
+        // A 4096-bit layout is rejected for a 2048-bit key
+        let big = LimbConfig::noir(4096).unwrap();
+        assert!(dkim_inputs(email.as_bytes(), Some(&key), &policy, Some(big)).is_err());
+        assert!(pubkey_inputs(&key.modulus, Some(big)).is_err());
+    }
+
+    #[test]
+    fn test_exports_report_error_codes() {
+        let err = parse_dkim_from_email(b"Subject: hi\r\n\r\nbody", None, None).unwrap_err();
+        assert_eq!(err, ZkWasmError::NoDkimSignature);
+
+        let email = dkim::tests::sign_email(dkim::tests::TEST_EMAIL);
+        let result = parse_dkim_from_email(email.as_bytes(), None, None).unwrap();
+        assert_eq!(result.pubkey_modulus(), Err(ZkWasmError::MissingKey));
+        let err = parse_dkim_from_email(email.as_bytes(), Some("gmail.com".into()), None)
+            .unwrap_err();
+        assert_eq!(err.code(), "NO_MATCHING_SIGNATURE");
+        assert_eq!(err.details()["available"], serde_json::json!(["example.com"]));
+
+        let err = compute_pubkey_inputs("not base64!", None).unwrap_err();
+        assert_eq!(err, ZkWasmError::InvalidBase64 { field: "pubkey" });
+
+        // From in the first block of a header longer than the circuit buffer
+        let mut header = b"From: a@example.com\r\n".to_vec();
+        header.extend(std::iter::repeat_n(b'x', 200));
+        let err = compute_partial_hash_for_email(&header, 64).unwrap_err();
+        assert_eq!(err.code(), "FROM_HEADER_TOO_EARLY");
+    }
+
+    #[test]
+    fn test_verifier_error_codes() {
+        use ark_serialize::CanonicalSerialize;
+
+        let mut verifier = ZKWASMVerifier::new();
+        assert_eq!(verifier.verify_proof(&[], &[]), Err(ZkWasmError::KeyNotLoaded));
+
+        let (_, vk) = zkey::tests::multiplier_zkey();
+        let mut vk_bytes = Vec::new();
+        vk.serialize_compressed(&mut vk_bytes).unwrap();
+        verifier.load_verifying_key(&vk_bytes).unwrap();
+        let err = verifier.verify_proof(b"not a proof", &[]).unwrap_err();
+        assert_eq!(err.code(), "MALFORMED_PROOF");
+        let err = verifier.load_verifying_key(b"junk").unwrap_err();
+        assert_eq!(err.code(), "MALFORMED_VERIFYING_KEY");
+
+        // A key without IC points would leave no room for the constant input
+        let mut empty_ic = vk.clone();
+        empty_ic.gamma_abc_g1.clear();
+        let mut empty_ic_bytes = Vec::new();
+        empty_ic.serialize_compressed(&mut empty_ic_bytes).unwrap();
+        let err = verifier.load_verifying_key(&empty_ic_bytes).unwrap_err();
+        assert_eq!(err.code(), "MALFORMED_VERIFYING_KEY");
+        assert!(!verify_proof(&empty_ic_bytes, &[], &[]).valid());
+
+        let err = groth16_error(SynthesisError::MalformedVerifyingKey);
+        assert_eq!(err.code(), "MALFORMED_VERIFYING_KEY");
+        assert!(!err.to_string().contains("MalformedVerifyingKey"), "{}", err);
+        let err = groth16_error(SynthesisError::UnexpectedIdentity);
+        assert_eq!(err.code(), "VERIFICATION_FAILED");
+    }
+
     #[test]
     fn test_find_from_header() {
         let email = b"Received: from test\r\nFrom: test@example.com\r\nTo: other@example.com";
@@ -744,4 +1601,112 @@ mod tests {
         let remaining_str = String::from_utf8_lossy(&result.remaining);
         assert!(remaining_str.contains("From:"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_prover_generates_verifiable_proof() {
+        use ark_serialize::CanonicalSerialize;
+
+        let r1cs = circom::parse_r1cs(&circom::tests::multiplier_r1cs()).unwrap();
+        let setup = prover::R1CSCircuit { r1cs: &r1cs, witness: None };
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(setup, &mut rand::rngs::OsRng).unwrap();
+        let mut pk_bytes = Vec::new();
+        pk.serialize_compressed(&mut pk_bytes).unwrap();
+
+        let mut zk_prover = ZKWASMProver::new();
+        assert_eq!(
+            zk_prover.prove_inputs(&serde_json::json!({})),
+            Err(ZkWasmError::ProvingKeyNotLoaded)
+        );
+        zk_prover.proving_key = Some(ProvingKey::deserialize_compressed(&pk_bytes[..]).unwrap());
+        zk_prover
+            .load_circuit(&circom::tests::multiplier_wasm(), &circom::tests::multiplier_r1cs())
+            .unwrap();
+
+        // The test witness generator truncates products to 64 bits
+        let overflow = serde_json::json!({ "a": "1099511627776", "b": "1099511627776" });
+        let err = zk_prover.prove_inputs(&overflow).unwrap_err();
+        assert_eq!(err.code(), "WITNESS_FAILED");
+        assert!(err.to_string().contains("Constraint 0 "), "{}", err);
+
+        let output = zk_prover.prove_inputs(&serde_json::json!({ "a": "7", "b": "6" })).unwrap();
+        assert_eq!(output["publicSignals"], serde_json::json!(["42"]));
+
+        let mut verifier = ZKWASMVerifier::new();
+        verifier.set_verifying_key(&vk);
+        assert!(verifier.verify_snarkjs(&output["proof"], &output["publicSignals"]).unwrap());
+        assert!(!verifier.verify_snarkjs(&output["proof"], &serde_json::json!(["43"])).unwrap());
+    }
+
+    #[test]
+    fn test_prover_accepts_snarkjs_zkey() {
+        let (zkey_bytes, vk) = zkey::tests::multiplier_zkey();
+        let mut zk_prover = ZKWASMProver::new();
+        zk_prover.load_proving_key(&zkey_bytes).unwrap();
+        zk_prover
+            .load_circuit(&circom::tests::multiplier_wasm(), &circom::tests::multiplier_r1cs())
+            .unwrap();
+
+        let output = zk_prover.prove_inputs(&serde_json::json!({ "a": "4", "b": "9" })).unwrap();
+        assert_eq!(output["publicSignals"], serde_json::json!(["36"]));
+
+        let mut verifier = ZKWASMVerifier::new();
+        let vk_json = snarkjs::verifying_key_to_json(&vk).to_string();
+        verifier.set_verifying_key(&snarkjs::verifying_key_from_json(&vk_json).unwrap());
+        assert!(verifier.verify_snarkjs(&output["proof"], &output["publicSignals"]).unwrap());
+        assert!(verifier.verify_snarkjs(&output["proof"], &serde_json::json!(["36", "1"])).is_err());
+
+        let second = zk_prover.prove_inputs(&serde_json::json!({ "a": "5", "b": "5" })).unwrap();
+        let proofs = [output["proof"].clone(), second["proof"].clone()];
+        let signals = [output["publicSignals"].clone(), second["publicSignals"].clone()];
+        assert!(verifier.verify_snarkjs_batch(&proofs, &signals).unwrap());
+        let swapped = [signals[1].clone(), signals[0].clone()];
+        assert!(!verifier.verify_snarkjs_batch(&proofs, &swapped).unwrap());
+    }
+
+    #[test]
+    fn test_free_verify_proof_checks_proofs() {
+        use ark_serialize::CanonicalSerialize;
+
+        let (zkey_bytes, vk) = zkey::tests::multiplier_zkey();
+        let mut zk_prover = ZKWASMProver::new();
+        zk_prover.load_proving_key(&zkey_bytes).unwrap();
+        zk_prover
+            .load_circuit(&circom::tests::multiplier_wasm(), &circom::tests::multiplier_r1cs())
+            .unwrap();
+        let output = zk_prover.prove_inputs(&serde_json::json!({ "a": "2", "b": "5" })).unwrap();
+
+        let vk_json = snarkjs::verifying_key_to_json(&vk).to_string();
+        let proof_json = output["proof"].to_string();
+        let result = verify_proof(vk_json.as_bytes(), proof_json.as_bytes(), b"[\"10\"]");
+        assert!(result.valid(), "{:?}", result.reason());
+
+        let result = verify_proof(vk_json.as_bytes(), proof_json.as_bytes(), b"[\"11\"]");
+        assert!(!result.valid());
+        assert!(result.reason().is_some());
+
+        // Compressed arkworks encodings are accepted too
+        let proof = snarkjs::proof_from_json(&output["proof"]).unwrap();
+        let (mut vk_bytes, mut proof_bytes, mut signal_bytes) = (Vec::new(), Vec::new(), Vec::new());
+        vk.serialize_compressed(&mut vk_bytes).unwrap();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        Fr::from(10u64).serialize_compressed(&mut signal_bytes).unwrap();
+        assert!(verify_proof(&vk_bytes, &proof_bytes, &signal_bytes).valid());
+
+        // Raw public inputs starting with `[` (a first signal of 91) are not
+        // mistaken for JSON
+        let output = zk_prover.prove_inputs(&serde_json::json!({ "a": "7", "b": "13" })).unwrap();
+        let proof = snarkjs::proof_from_json(&output["proof"]).unwrap();
+        let (mut proof_bytes, mut signal_bytes) = (Vec::new(), Vec::new());
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        Fr::from(91u64).serialize_compressed(&mut signal_bytes).unwrap();
+        assert_eq!(signal_bytes[0], b'[');
+        let result = verify_proof(&vk_bytes, &proof_bytes, &signal_bytes);
+        assert!(result.valid(), "{:?}", result.reason());
+        let result = verify_proof(vk_json.as_bytes(), proof_json.as_bytes(), b"[\"10\"");
+        assert!(result.reason().unwrap().contains("Invalid public signals JSON"));
+
+        let result = verify_proof(b"", &proof_bytes, &signal_bytes);
+        assert!(!result.valid());
+        assert!(result.reason().unwrap().contains("verifying key"));
+    }
+}
@@ -0,0 +1,200 @@
+// ============================================================================
+// Limb layouts for big integers in circuits
+// ============================================================================
+
+use crate::error::ZkWasmError;
+use num_bigint::BigUint;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// How a circuit splits RSA values into field-element limbs
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimbConfig {
+    limb_bits: usize,
+    num_limbs: usize,
+    modulus_bits: usize,
+}
+
+impl LimbConfig {
+    /// Noir `BigNum` 2048-bit layout used by the zkemail.nr `RSAPubkey`
+    pub const NOIR_2048: Self = Self {
+        limb_bits: 120,
+        num_limbs: 18,
+        modulus_bits: 2048,
+    };
+
+    /// circom `EmailVerifier(n = 121, k = 17)`
+    pub const CIRCOM_2048: Self = Self {
+        limb_bits: 121,
+        num_limbs: 17,
+        modulus_bits: 2048,
+    };
+
+    /// Noir overflow bits added to the Barrett parameter by `noir-bignum-paramgen`
+    const REDC_OVERFLOW_BITS: usize = 4;
+
+    /// Split `value` into `num_limbs` little-endian limbs, failing if it does not fit
+    pub fn to_limbs(&self, value: &BigUint) -> Result<Vec<String>, ZkWasmError> {
+        let capacity = self.limb_bits * self.num_limbs;
+        if value.bits() as usize > capacity {
+            return Err(ZkWasmError::LimbLayout(format!(
+                "A {}-bit value does not fit in {} limbs of {} bits",
+                value.bits(),
+                self.num_limbs,
+                self.limb_bits
+            )));
+        }
+        let mask = (BigUint::from(1u32) << self.limb_bits) - 1u32;
+        Ok((0..self.num_limbs)
+            .map(|i| ((value >> (i * self.limb_bits)) & &mask).to_string())
+            .collect())
+    }
+
+    /// Barrett reduction parameter used by Noir's BigNum:
+    /// `floor(2^(2 * modulus_bits + 4) / modulus)`.
+    ///
+    /// `modulus_bits` is the circuit's declared size (e.g. 2048), not
+    /// `modulus.bits()` and not the limb capacity.
+    pub fn redc_param(&self, modulus: &BigUint) -> Result<Vec<String>, ZkWasmError> {
+        let shift = 2 * self.modulus_bits + Self::REDC_OVERFLOW_BITS;
+        self.to_limbs(&((BigUint::from(1u32) << shift) / modulus))
+    }
+
+    /// Check that `modulus` has the size this layout was made for
+    pub fn check_modulus(&self, modulus: &BigUint) -> Result<(), ZkWasmError> {
+        let bits = modulus.bits() as usize;
+        // Real keys may have a few leading zero bits, but never a whole byte less
+        if bits > self.modulus_bits || bits + 8 <= self.modulus_bits {
+            return Err(ZkWasmError::LimbLayout(format!(
+                "A {}-bit RSA modulus does not match the {}-bit limb layout",
+                bits, self.modulus_bits
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl LimbConfig {
+    /// Custom layout of `num_limbs` limbs of `limb_bits` bits for `modulus_bits`-bit keys
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(limb_bits: usize, num_limbs: usize, modulus_bits: usize) -> Result<LimbConfig, ZkWasmError> {
+        // Limbs are field elements, and pairs are packed for hashing
+        if limb_bits == 0 || limb_bits > 126 {
+            return Err(ZkWasmError::LimbLayout(format!(
+                "Limb size must be 1 to 126 bits, got {}",
+                limb_bits
+            )));
+        }
+        if limb_bits * num_limbs < modulus_bits {
+            return Err(ZkWasmError::LimbLayout(format!(
+                "{} limbs of {} bits cannot hold a {}-bit modulus",
+                num_limbs, limb_bits, modulus_bits
+            )));
+        }
+        Ok(Self {
+            limb_bits,
+            num_limbs,
+            modulus_bits,
+        })
+    }
+
+    /// Noir BigNum layout (120-bit limbs) for 1024, 2048 or 4096-bit keys
+    pub fn noir(modulus_bits: usize) -> Result<LimbConfig, ZkWasmError> {
+        check_key_size(modulus_bits)?;
+        Self::new(120, modulus_bits.div_ceil(120), modulus_bits)
+    }
+
+    /// circom layout (121-bit limbs) for 1024, 2048 or 4096-bit keys
+    pub fn circom(modulus_bits: usize) -> Result<LimbConfig, ZkWasmError> {
+        check_key_size(modulus_bits)?;
+        Self::new(121, modulus_bits.div_ceil(121), modulus_bits)
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn limb_bits(&self) -> usize {
+        self.limb_bits
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn num_limbs(&self) -> usize {
+        self.num_limbs
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn modulus_bits(&self) -> usize {
+        self.modulus_bits
+    }
+}
+
+fn check_key_size(modulus_bits: usize) -> Result<(), ZkWasmError> {
+    match modulus_bits {
+        1024 | 2048 | 4096 => Ok(()),
+        bits => Err(ZkWasmError::UnsupportedKeySize { bits }),
+    }
+}
+
+/// Standard RSA size of a modulus or signature: its bit length rounded up to
+/// 1024, 2048 or 4096
+pub fn rsa_key_size(value: &BigUint) -> Result<usize, ZkWasmError> {
+    let bits = value.bits() as usize;
+    [1024, 2048, 4096]
+        .into_iter()
+        .find(|size| bits <= *size)
+        .ok_or(ZkWasmError::UnsupportedKeySize { bits })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(LimbConfig::noir(2048).unwrap(), LimbConfig::NOIR_2048);
+        assert_eq!(LimbConfig::circom(2048).unwrap(), LimbConfig::CIRCOM_2048);
+        assert_eq!(LimbConfig::noir(1024).unwrap().num_limbs(), 9);
+        assert_eq!(LimbConfig::noir(4096).unwrap().num_limbs(), 35);
+        assert_eq!(LimbConfig::circom(4096).unwrap().num_limbs(), 34);
+        assert!(LimbConfig::noir(3072).is_err());
+        assert!(LimbConfig::new(64, 4, 2048).is_err());
+    }
+
+    #[test]
+    fn test_limbs_and_redc() {
+        let (key, _) = crate::rsa::tests::test_key();
+        let noir = LimbConfig::NOIR_2048;
+        let limbs = noir.to_limbs(&key.modulus).unwrap();
+        assert_eq!(limbs.len(), 18);
+        let rebuilt = limbs.iter().rev().fold(BigUint::from(0u32), |acc, limb| {
+            (acc << 120) + limb.parse::<BigUint>().unwrap()
+        });
+        assert_eq!(rebuilt, key.modulus);
+
+        let redc = noir.redc_param(&key.modulus).unwrap();
+        let expected = (BigUint::from(1u32) << 4100) / &key.modulus;
+        assert_eq!(redc, noir.to_limbs(&expected).unwrap());
+
+        assert_eq!(LimbConfig::CIRCOM_2048.to_limbs(&key.modulus).unwrap().len(), 17);
+        assert!(noir.check_modulus(&key.modulus).is_ok());
+        assert!(LimbConfig::noir(1024).unwrap().to_limbs(&key.modulus).is_err());
+        assert!(LimbConfig::noir(4096).unwrap().check_modulus(&key.modulus).is_err());
+    }
+
+    #[test]
+    fn test_other_key_sizes() {
+        for bits in [1024usize, 4096] {
+            let modulus = (BigUint::from(1u32) << (bits - 1)) + 12345u32;
+            assert_eq!(rsa_key_size(&modulus).unwrap(), bits);
+            for config in [LimbConfig::noir(bits).unwrap(), LimbConfig::circom(bits).unwrap()] {
+                config.check_modulus(&modulus).unwrap();
+                assert_eq!(config.to_limbs(&modulus).unwrap().len(), config.num_limbs());
+                assert_eq!(config.redc_param(&modulus).unwrap().len(), config.num_limbs());
+            }
+        }
+        assert_eq!(
+            rsa_key_size(&(BigUint::from(1u32) << 4096)),
+            Err(ZkWasmError::UnsupportedKeySize { bits: 4097 })
+        );
+    }
+}
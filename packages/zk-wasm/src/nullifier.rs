@@ -0,0 +1,317 @@
+// ============================================================================
+// Nullifiers (circuits/src/nullifier.circom)
+// ============================================================================
+
+use crate::poseidon::{poseidon, poseidon_modular};
+use crate::snarkjs::field_from_decimal;
+use ark_bn254::Fr;
+use ark_ff::{PrimeField, Zero};
+use ark_std::UniformRand;
+use rand::rngs::OsRng;
+use serde_json::Value;
+
+/// `EmailNullifier(k)`: `PoseidonModular(k)` over the RSA signature limbs.
+///
+/// A DKIM signature is unique per email and private to its recipient, so the
+/// nullifier identifies the email without revealing it. Each email from the
+/// same student gives another value.
+pub fn email_nullifier(signature_limbs: &[Fr]) -> Result<Fr, String> {
+    poseidon_modular(signature_limbs)
+}
+
+/// `ExternalNullifier(k)`: `Poseidon(email_nullifier, faculty_id, semester)`,
+/// which differs per faculty and semester for the same email
+pub fn external_nullifier(
+    signature_limbs: &[Fr],
+    faculty_id: Fr,
+    semester: Fr,
+) -> Result<Fr, String> {
+    scope(email_nullifier(signature_limbs)?, faculty_id, semester)
+}
+
+/// The last step of `ExternalNullifier`: `Poseidon(3)` over the email
+/// nullifier, faculty id and semester
+fn scope(email_nullifier: Fr, faculty_id: Fr, semester: Fr) -> Result<Fr, String> {
+    poseidon(&[email_nullifier, faculty_id, semester])
+}
+
+/// Longest From address `ReviewNullifier` takes, as in `bracu_review_verifier.circom`
+pub const MAX_ADDRESS_BYTES: usize = 64;
+
+/// Bytes per field element in zk-email's `PackBytes`
+const PACK_BYTES: usize = 31;
+
+/// `AddressHash(MAX_ADDRESS_BYTES)`: the address zero-filled to
+/// `MAX_ADDRESS_BYTES`, packed by `PackBytes` (31 bytes per element, first
+/// byte lowest) and hashed with `PoseidonModular`.
+///
+/// The bytes are hashed as they appear in the signed From field, exactly as
+/// the circuit reveals them.
+pub fn address_hash(address: &[u8]) -> Result<Fr, String> {
+    if address.is_empty() || address.len() > MAX_ADDRESS_BYTES || !address.contains(&b'@') {
+        return Err(format!(
+            "'{}' is not an email address of at most {} bytes",
+            String::from_utf8_lossy(address),
+            MAX_ADDRESS_BYTES
+        ));
+    }
+    let mut padded = address.to_vec();
+    padded.resize(MAX_ADDRESS_BYTES, 0);
+    let packed: Vec<Fr> = padded
+        .chunks(PACK_BYTES)
+        .map(Fr::from_le_bytes_mod_order)
+        .collect();
+    poseidon_modular(&packed)
+}
+
+/// Domain tags, so nullifiers never collide with other Poseidon hashes of
+/// the same values
+const ADDRESS_DOMAIN: &str = "bracu-address-nullifier-v1";
+const REVIEW_DOMAIN: &str = "bracu-review-nullifier-v1";
+
+/// `AddressNullifier(MAX_ADDRESS_BYTES)`: `Poseidon(domain,
+/// AddressHash(from_address), secret)`, a student's email nullifier.
+///
+/// `secret` is a random field element the student keeps and never sends, so
+/// knowing the address, as anyone with the roster does, is not enough to
+/// compute it. The signed From address ties the value to one student; keeping
+/// the same secret gives the same value for every email they prove with. The
+/// circuit cannot tell a second secret from the first, so a new secret also
+/// gives a new value.
+pub fn address_nullifier(from_address: &[u8], secret: Fr) -> Result<Fr, String> {
+    if secret.is_zero() {
+        return Err("The nullifier secret must not be zero".to_string());
+    }
+    poseidon(&[
+        pack_label(ADDRESS_DOMAIN)?,
+        address_hash(from_address)?,
+        secret,
+    ])
+}
+
+/// The `faculty` and `semester` public inputs of `ReviewNullifier`. Faculty
+/// initials and the semester are trimmed and uppercased first, so "abc" and
+/// "ABC" name the same faculty.
+pub fn review_scope(faculty_initials: &str, semester: &str) -> Result<(Fr, Fr), String> {
+    Ok((
+        pack_label(&faculty_initials.trim().to_ascii_uppercase())?,
+        pack_label(&semester.trim().to_ascii_uppercase())?,
+    ))
+}
+
+/// `ReviewNullifier`: `Poseidon(domain, email_nullifier, faculty, semester)`,
+/// the only nullifier `BracuReviewVerifier` makes public.
+///
+/// `email_nullifier` is the student's `address_nullifier`, so they get one
+/// value per faculty and semester, and values for different faculty cannot be
+/// linked without their secret.
+pub fn review_nullifier(
+    email_nullifier: Fr,
+    faculty_initials: &str,
+    semester: &str,
+) -> Result<Fr, String> {
+    let (faculty, semester) = review_scope(faculty_initials, semester)?;
+    poseidon(&[pack_label(REVIEW_DOMAIN)?, email_nullifier, faculty, semester])
+}
+
+/// A random secret for `address_nullifier`, as a field element
+pub fn random_secret() -> Fr {
+    Fr::rand(&mut OsRng)
+}
+
+/// A short label as one field element: its bytes big-endian, behind a
+/// leading 1 so that "A" and "\0A" differ
+fn pack_label(label: &str) -> Result<Fr, String> {
+    if label.is_empty() || label.len() > 30 {
+        return Err(format!("'{}' must be 1 to 30 bytes long", label));
+    }
+    let mut bytes = vec![1u8];
+    bytes.extend_from_slice(label.as_bytes());
+    Ok(Fr::from_be_bytes_mod_order(&bytes))
+}
+
+/// Decimal limbs, as in `DKIMResult.signature`, to field elements
+pub fn parse_field_elements(values: &[String]) -> Result<Vec<Fr>, String> {
+    values.iter().map(|value| parse_field(value)).collect()
+}
+
+pub fn parse_field(value: &str) -> Result<Fr, String> {
+    field_from_decimal(&Value::from(value.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit_inputs::{circom_inputs, circom_review_inputs};
+    use crate::dkim::tests::{sign_email, TEST_EMAIL};
+    use crate::dkim::SignaturePolicy;
+    use crate::rsa::tests::test_key;
+
+    fn signature_limbs(email: &str) -> Vec<Fr> {
+        let (key, _) = test_key();
+        let inputs =
+            circom_inputs(email.as_bytes(), &key, &SignaturePolicy::FromAligned, 1024).unwrap();
+        parse_field_elements(&inputs.signature).unwrap()
+    }
+
+    #[test]
+    fn test_email_nullifier() {
+        let limbs = signature_limbs(&sign_email(TEST_EMAIL));
+        assert_eq!(limbs.len(), 17);
+        let nullifier = email_nullifier(&limbs).unwrap();
+        assert_eq!(
+            nullifier,
+            poseidon(&[
+                poseidon(&limbs[..16]).unwrap(),
+                poseidon(&limbs[16..]).unwrap()
+            ])
+            .unwrap()
+        );
+
+        // Another email from the same sender and key gets another nullifier
+        let other = signature_limbs(&sign_email(&TEST_EMAIL.replace("Exam", "Quiz")));
+        assert_ne!(email_nullifier(&other).unwrap(), nullifier);
+    }
+
+    /// circomlibjs `poseidon([1, ..., 16])` and `poseidon([1, 2, 3])`, as
+    /// published in go-iden3-crypto `poseidon/poseidon_test.go` and circomlibjs
+    /// `test/poseidon.js`: the widths 17 and 4 behind `EmailNullifier` and
+    /// `ExternalNullifier`
+    #[test]
+    fn test_circomlibjs_vectors() {
+        let limbs: Vec<String> = (1..=16).map(|i| i.to_string()).collect();
+        assert_eq!(
+            crate::compute_email_nullifier(limbs).unwrap(),
+            "9989051620750914585850546081941653841776809718687451684622678807385399211877"
+        );
+        let scoped = scope(Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)).unwrap();
+        assert_eq!(
+            crate::snarkjs::field_to_decimal(&scoped),
+            "6542985608222806190361240322586112750744169038454362455181422643027100751666"
+        );
+    }
+
+    #[test]
+    fn test_external_nullifier() {
+        let limbs = signature_limbs(&sign_email(TEST_EMAIL));
+        let scoped = |faculty: u64, semester: u64| {
+            external_nullifier(&limbs, Fr::from(faculty), Fr::from(semester)).unwrap()
+        };
+        assert_eq!(scoped(7, 20251), scoped(7, 20251));
+        assert_ne!(scoped(7, 20251), scoped(8, 20251));
+        assert_ne!(scoped(7, 20251), scoped(7, 20252));
+        assert_ne!(scoped(7, 20251), email_nullifier(&limbs).unwrap());
+
+        assert!(parse_field("12x").is_err());
+        assert_eq!(parse_field(" 42 ").unwrap(), Fr::from(42u64));
+    }
+
+    #[test]
+    fn test_review_nullifier() {
+        let address = b"student@g.bracu.ac.bd";
+        let secret = Fr::from(20_251_001u64);
+        let email_nullifier = address_nullifier(address, secret).unwrap();
+        let review = |faculty: &str, semester: &str| {
+            review_nullifier(email_nullifier, faculty, semester).unwrap()
+        };
+
+        assert_eq!(review("ABC", "Fall 2025"), review(" abc ", "FALL 2025"));
+        assert_ne!(review("ABC", "Fall 2025"), review("ABD", "Fall 2025"));
+        assert_ne!(review("ABC", "Fall 2025"), review("ABC", "Spring 2026"));
+        // Fields cannot be shifted between faculty and semester
+        assert_ne!(review("AB", "C"), review("A", "BC"));
+        // Another student reviewing the same faculty gets another nullifier
+        let other = address_nullifier(b"other@g.bracu.ac.bd", secret).unwrap();
+        assert_ne!(
+            review_nullifier(other, "ABC", "Fall 2025").unwrap(),
+            review("ABC", "Fall 2025")
+        );
+
+        assert!(review_nullifier(email_nullifier, "", "Fall 2025").is_err());
+        assert!(review_nullifier(email_nullifier, "ABC", &"x".repeat(31)).is_err());
+        assert!(address_nullifier(b"student", secret).is_err());
+        assert!(address_nullifier(&[b'a'; 65], secret).is_err());
+        assert!(address_nullifier(address, Fr::zero()).is_err());
+    }
+
+    /// Knowing a student's address does not give their nullifiers: without
+    /// the secret, every guess at the email nullifier misses
+    #[test]
+    fn test_address_alone_does_not_give_the_nullifier() {
+        let address = b"student@g.bracu.ac.bd";
+        let secret = random_secret();
+        let email_nullifier = address_nullifier(address, secret).unwrap();
+        let review = review_nullifier(email_nullifier, "ABC", "Fall 2025").unwrap();
+
+        let hash = address_hash(address).unwrap();
+        let guesses = [
+            hash,
+            poseidon(&[pack_label(ADDRESS_DOMAIN).unwrap(), hash]).unwrap(),
+            address_nullifier(address, Fr::from(1u64)).unwrap(),
+            address_nullifier(address, random_secret()).unwrap(),
+        ];
+        for guess in guesses {
+            assert_ne!(review_nullifier(guess, "ABC", "Fall 2025").unwrap(), review);
+        }
+        assert_ne!(random_secret(), secret);
+    }
+
+    #[test]
+    fn test_review_nullifier_is_per_student() {
+        let secret = Fr::from(20_251_001u64);
+        let inputs = |email: &str| {
+            let (key, _) = test_key();
+            circom_review_inputs(
+                email.as_bytes(),
+                &key,
+                &SignaturePolicy::FromAligned,
+                1024,
+                secret,
+                "ABC",
+                "Fall 2025",
+            )
+            .unwrap()
+        };
+        let first = inputs(&sign_email(TEST_EMAIL));
+        let address = &first.circom.in_padded[first.from_address_index..]
+            [..first.from_address_length];
+        let email_nullifier = address_nullifier(address, secret).unwrap();
+        assert_eq!(
+            first.review_nullifier,
+            crate::snarkjs::field_to_decimal(
+                &review_nullifier(email_nullifier, "ABC", "Fall 2025").unwrap()
+            )
+        );
+
+        // Two emails from the same student give the same review nullifier
+        let second = inputs(&sign_email(&TEST_EMAIL.replace("Exam", "Quiz")));
+        assert_ne!(first.circom.signature, second.circom.signature);
+        assert_eq!(first.review_nullifier, second.review_nullifier);
+    }
+
+    /// `ADDRESS_DOMAIN` and `REVIEW_DOMAIN` in `AddressNullifier` and
+    /// `ReviewNullifier`
+    #[test]
+    fn test_domains_match_circuit() {
+        let decimal = |label| crate::snarkjs::field_to_decimal(&pack_label(label).unwrap());
+        assert_eq!(
+            decimal(ADDRESS_DOMAIN),
+            "569574045224753442742071884167210502946750949534630645544613425"
+        );
+        assert_eq!(
+            decimal(REVIEW_DOMAIN),
+            "2224898614159193514917112078108738299973290827250918507771441"
+        );
+    }
+
+    /// `PackBytes` puts the first byte of each 31-byte chunk lowest
+    #[test]
+    fn test_address_hash_packing() {
+        let address = b"a@b";
+        let packed = Fr::from(u64::from(b'a') + (u64::from(b'@') << 8) + (u64::from(b'b') << 16));
+        assert_eq!(
+            address_hash(address).unwrap(),
+            poseidon(&[packed, Fr::from(0u64), Fr::from(0u64)]).unwrap()
+        );
+    }
+}
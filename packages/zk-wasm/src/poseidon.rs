@@ -0,0 +1,347 @@
+// ============================================================================
+// Poseidon over BN254 (circomlib parameters)
+// ============================================================================
+
+use ark_bn254::Fr;
+use ark_ff::{Field, PrimeField, Zero};
+use num_bigint::BigUint;
+use std::sync::OnceLock;
+
+/// Full rounds, the same for every width
+const ROUNDS_F: usize = 8;
+/// Partial rounds for widths `t = 2..=17`, as in circomlib
+const ROUNDS_P: [usize; 16] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68];
+
+/// Round constants and MDS matrix for one width
+struct Params {
+    round_constants: Vec<Fr>,
+    mds: Vec<Vec<Fr>>,
+}
+
+static PARAMS: [OnceLock<Params>; 16] = [const { OnceLock::new() }; 16];
+
+/// Poseidon hash of 1 to 16 field elements, identical to circomlib's
+/// `Poseidon(n)` template and circomlibjs `poseidon`
+pub fn poseidon(inputs: &[Fr]) -> Result<Fr, String> {
+    if inputs.is_empty() || inputs.len() > ROUNDS_P.len() {
+        return Err(format!("Poseidon takes 1 to 16 inputs, got {}", inputs.len()));
+    }
+    let t = inputs.len() + 1;
+    let params = PARAMS[t - 2].get_or_init(|| Params::generate(t));
+    let rounds_p = ROUNDS_P[t - 2];
+
+    let mut state = Vec::with_capacity(t);
+    state.push(Fr::zero());
+    state.extend_from_slice(inputs);
+
+    for round in 0..ROUNDS_F + rounds_p {
+        for (i, word) in state.iter_mut().enumerate() {
+            *word += params.round_constants[round * t + i];
+        }
+        if round < ROUNDS_F / 2 || round >= ROUNDS_F / 2 + rounds_p {
+            state.iter_mut().for_each(|word| *word = word.pow([5]));
+        } else {
+            state[0] = state[0].pow([5]);
+        }
+        state = params
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(&state).map(|(m, word)| *m * word).sum())
+            .collect();
+    }
+    Ok(state[0])
+}
+
+impl Params {
+    /// Derive the parameters with the Grain LFSR of the Poseidon reference
+    /// script (`generate_parameters_grain.sage`), which is how circomlib's
+    /// constants were produced.
+    ///
+    /// The script re-samples the MDS matrix when it fails its subspace trail
+    /// checks, which are not implemented here. Every width the circuits use
+    /// (2, 4, 10 and 17) is checked against circomlib in the tests below;
+    /// other widths are only as good as the same generator.
+    fn generate(t: usize) -> Self {
+        let rounds_p = ROUNDS_P[t - 2];
+        let field_bits = Fr::MODULUS_BIT_SIZE as usize;
+        let modulus: BigUint = Fr::MODULUS.into();
+        let mut grain = Grain::new(field_bits, t, ROUNDS_F, rounds_p);
+
+        let round_constants = (0..(ROUNDS_F + rounds_p) * t)
+            .map(|_| loop {
+                let value = grain.next_integer(field_bits);
+                if value < modulus {
+                    break Fr::from(value);
+                }
+            })
+            .collect();
+
+        // Cauchy matrix 1 / (x_i + y_j) over distinct random elements
+        let points: Vec<Fr> = loop {
+            let points: Vec<Fr> = (0..2 * t)
+                .map(|_| Fr::from(grain.next_integer(field_bits)))
+                .collect();
+            let distinct = points
+                .iter()
+                .enumerate()
+                .all(|(i, p)| !points[..i].contains(p));
+            if distinct {
+                break points;
+            }
+        };
+        let (xs, ys) = points.split_at(t);
+        let mds = xs
+            .iter()
+            .map(|x| {
+                ys.iter()
+                    .map(|y| (*x + y).inverse().expect("x_i + y_j is non-zero"))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            round_constants,
+            mds,
+        }
+    }
+}
+
+/// 80-bit Grain LFSR in self-shrinking mode
+struct Grain {
+    /// Bit `i` is the `i`-th oldest bit of the sequence
+    state: u128,
+}
+
+impl Grain {
+    fn new(field_bits: usize, t: usize, rounds_f: usize, rounds_p: usize) -> Self {
+        // field = 1 (prime), sbox = 0 (x^alpha), then sizes and 30 ones
+        let fields: [(u128, usize); 7] = [
+            (1, 2),
+            (0, 4),
+            (field_bits as u128, 12),
+            (t as u128, 12),
+            (rounds_f as u128, 10),
+            (rounds_p as u128, 10),
+            ((1 << 30) - 1, 30),
+        ];
+        let mut state = 0u128;
+        let mut position = 0;
+        for (value, width) in fields {
+            for bit in (0..width).rev() {
+                state |= ((value >> bit) & 1) << position;
+                position += 1;
+            }
+        }
+
+        let mut grain = Self { state };
+        for _ in 0..160 {
+            grain.clock();
+        }
+        grain
+    }
+
+    fn clock(&mut self) -> u128 {
+        let bit = |i: u32| (self.state >> i) & 1;
+        let new_bit = bit(62) ^ bit(51) ^ bit(38) ^ bit(23) ^ bit(13) ^ bit(0);
+        self.state = (self.state >> 1) | (new_bit << 79);
+        new_bit
+    }
+
+    /// Bits are produced in pairs; the second is kept only when the first is 1
+    fn next_bit(&mut self) -> u128 {
+        loop {
+            let keep = self.clock();
+            let bit = self.clock();
+            if keep == 1 {
+                return bit;
+            }
+        }
+    }
+
+    /// Big-endian integer made of the next `bits` output bits
+    fn next_integer(&mut self, bits: usize) -> BigUint {
+        let mut value = BigUint::zero();
+        for _ in 0..bits {
+            value = (value << 1u32) | BigUint::from(self.next_bit() as u8);
+        }
+        value
+    }
+}
+
+/// zk-email's `PoseidonLarge(n, k)`: pairs of `n`-bit limbs are packed as
+/// `in[2i] + 2^n * in[2i + 1]` (the last limb alone when `k` is odd) and the
+/// packed values are hashed with `Poseidon(ceil(k / 2))`.
+pub fn poseidon_large(limbs: &[Fr], limb_bits: usize) -> Result<Fr, String> {
+    let k = limbs.len();
+    if !(17..=32).contains(&k) {
+        return Err(format!("PoseidonLarge takes 17 to 32 limbs, got {}", k));
+    }
+    if 2 * limb_bits >= Fr::MODULUS_BIT_SIZE as usize {
+        return Err(format!("Two {}-bit limbs do not fit in one field element", limb_bits));
+    }
+    let shift = Fr::from(BigUint::from(1u32) << limb_bits);
+    let packed: Vec<Fr> = limbs
+        .chunks(2)
+        .map(|pair| match pair {
+            [low, high] => *low + shift * high,
+            [low] => *low,
+            _ => unreachable!(),
+        })
+        .collect();
+    poseidon(&packed)
+}
+
+/// Poseidon of any number of elements, as `PoseidonModular(n)` in
+/// `nullifier.circom`: chunks of 16 (the last one shorter) are hashed
+/// separately and chained as `acc = Poseidon(acc, chunk_hash)`.
+pub fn poseidon_modular(inputs: &[Fr]) -> Result<Fr, String> {
+    let mut chunks = inputs.chunks(ROUNDS_P.len());
+    let first = chunks
+        .next()
+        .ok_or_else(|| "PoseidonModular takes at least one input".to_string())?;
+    chunks.try_fold(poseidon(first)?, |acc, chunk| poseidon(&[acc, poseidon(chunk)?]))
+}
+
+/// The circuit's `pubkey_hash`: `PoseidonLarge(n, k)` over the RSA modulus
+/// split into `k` little-endian limbs of `n` bits
+pub fn pubkey_hash(modulus: &BigUint, n: usize, k: usize) -> Result<Fr, String> {
+    if modulus.bits() as usize > n * k {
+        return Err(format!(
+            "A {}-bit modulus does not fit in {} limbs of {} bits",
+            modulus.bits(),
+            k,
+            n
+        ));
+    }
+    let mask = (BigUint::from(1u32) << n) - 1u32;
+    let limbs: Vec<Fr> = (0..k)
+        .map(|i| Fr::from((modulus >> (i * n)) & &mask))
+        .collect();
+    poseidon_large(&limbs, n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snarkjs::field_to_decimal;
+
+    fn hash(inputs: &[u64]) -> String {
+        let inputs: Vec<Fr> = inputs.iter().map(|v| Fr::from(*v)).collect();
+        field_to_decimal(&poseidon(&inputs).unwrap())
+    }
+
+    /// Outputs of circomlibjs `poseidon`, as published in circomlib
+    /// `test/poseidoncircuit.js`, circomlibjs `test/poseidon.js` and
+    /// go-iden3-crypto `poseidon/poseidon_test.go` (which share circomlib's
+    /// `poseidon_constants`). They cover widths 2, 3, 4, 5, 6, 7, 15 and 17,
+    /// including every width of `EmailNullifier` and `ExternalNullifier`.
+    #[test]
+    fn test_circomlib_vectors() {
+        let vectors: [(&[u64], &str); 10] = [
+            (&[1], "18586133768512220936620570745912940619677854269274689475585506675881198879027"),
+            (&[1, 2], "7853200120776062878684798364095072458815029376092732009249414926327459813530"),
+            (&[3, 4], "14763215145315200506921711489642608356394854266165572616578112107564877678998"),
+            (&[1, 2, 3], "6542985608222806190361240322586112750744169038454362455181422643027100751666"),
+            (&[1, 2, 3, 4], "18821383157269793795438455681495246036402687001665670618754263018637548127333"),
+            (&[1, 2, 0, 0, 0], "1018317224307729531995786483840663576608797660851238720571059489595066344487"),
+            (&[3, 4, 0, 0, 0], "5811595552068139067952687508729883632420015185677766880877743348592482390548"),
+            (&[1, 2, 3, 4, 5, 6], "20400040500897583745843009878988256314335038853985262692600694741116813247201"),
+            (
+                &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14],
+                "8354478399926161176778659061636406690034081872658507739535256090879947077494",
+            ),
+            (
+                &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+                "9989051620750914585850546081941653841776809718687451684622678807385399211877",
+            ),
+        ];
+        for (inputs, expected) in vectors {
+            assert_eq!(hash(inputs), expected, "poseidon({:?})", inputs);
+        }
+        assert!(poseidon(&[]).is_err());
+        assert!(poseidon(&[Fr::zero(); 17]).is_err());
+    }
+
+    /// Width 10 is what `pubkey_hash` uses for 2048-bit keys. The first row
+    /// and column of the MDS matrix are copied from `x5_10_config` of
+    /// noir-lang/poseidon v0.1.0 (the circomlib constants behind `hash_9`), as
+    /// compiled into `circuit-artifacts/bracu_verifier.json`.
+    ///
+    /// The matrix is Cauchy, `1 / (x_i + y_j)`, so its first row and column
+    /// fix every entry. Its points are drawn from the Grain stream after all
+    /// round constants, so they also only match if the constants before them
+    /// were drawn the same way.
+    #[test]
+    fn test_width_10_matches_noir_poseidon() {
+        let params = Params::generate(10);
+        let hex = |s: &str| Fr::from(BigUint::parse_bytes(s.as_bytes(), 16).unwrap());
+        let first_row: Vec<Fr> = [
+            "0b1e797eee3f15b88ce3a1fafec88da1d6329ac3c34712db570de886cf569098",
+            "2fe429db8068e61b82624f25766273cd9fe6368ff097eab97aaa09be4c630e27",
+            "2e750867942439083365d21fe760c7dfec9e3593c67de12a4255c6b01648b690",
+            "23237985a96b177ff7c01486ab0d603d29991b8324abd6b07dbb96f5c21ba17f",
+            "15ccec857b25934ffd429af92c910db31d00dc1c3fb83745a31d8131ea5baa85",
+            "17102ad2d98043e5509e76c331e6b22d5fb62d074760806aa675764660eb56d2",
+            "0a4ecaabde9d1ea11c81dafa8d5d4869bee7ad0bf4eb78397b71b8f989cf9812",
+            "0ec1821648fddc448a1512aecc8ac37714923981940f5542737a468d4d887c8f",
+            "255a493aa4becd9d137eb76941c4120784c1233f6c5fdf5d65c0832af543e415",
+            "120f44670301b1435249294d9833d59e01682af4c8658719727678fd9ab7ef2e",
+        ]
+        .iter()
+        .map(|value| hex(value))
+        .collect();
+        let first_column: Vec<Fr> = [
+            "1abb95b6e33243a050e563b5c4fd059de5513ec28994a6f60e57c98d3bc8f586",
+            "110d213a836478aa301bc1fd4edd388507d87745b3abfba5bf282afd21f7242a",
+            "18edb2d105bb53b768ae0137850dc2d2bf6d1303a1f8ee4aadf76d8f81990588",
+            "1e53e92df6c53ecec4c0de5b07dc4c9a0c5545f2abcc1fabf4ccb84a0a6e2c27",
+            "10fcb817e10f69f7074e64c393b807cdddf33c84a8b5cfe33f44b73fe18c0e36",
+            "2b1d8c11fc652c593543b6f9800bb7d0c9371dedb96f08bbbccffdc6cc44db98",
+            "19f473e40c05676d5f8d51e11336e9c65cb32828c397cc8125f1dc667b6cadf7",
+            "0fcf3a4d9c6c3392de1bdfe25cfb86817ecb80c51be17d47f79750201f1fcd01",
+            "23b02d00fe2634889673a7d04736fe15b9f62652b1c0626a19af8d6085d70822",
+        ]
+        .iter()
+        .map(|value| hex(value))
+        .collect();
+
+        // x_i + y_j = (x_i + y_0) + (x_0 + y_j) - (x_0 + y_0)
+        let corner = first_row[0].inverse().unwrap();
+        for i in 0..10 {
+            let row_sum = match i {
+                0 => corner,
+                _ => first_column[i - 1].inverse().unwrap(),
+            };
+            for (j, entry) in first_row.iter().enumerate() {
+                let expected = (row_sum + entry.inverse().unwrap() - corner).inverse().unwrap();
+                assert_eq!(params.mds[i][j], expected, "mds[{}][{}]", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pubkey_hash_packs_limb_pairs() {
+        let (key, _) = crate::rsa::tests::test_key();
+        let hash = pubkey_hash(&key.modulus, 121, 17).unwrap();
+
+        // Packing pairs of 121-bit limbs is the same as splitting into 242-bit chunks
+        let mask = (BigUint::from(1u32) << 242) - 1u32;
+        let packed: Vec<Fr> = (0..9)
+            .map(|i| Fr::from((&key.modulus >> (i * 242)) & &mask))
+            .collect();
+        assert_eq!(hash, poseidon(&packed).unwrap());
+
+        assert!(pubkey_hash(&key.modulus, 121, 16).is_err());
+        assert!(pubkey_hash(&key.modulus, 64, 33).is_err());
+    }
+
+    #[test]
+    fn test_poseidon_modular_chains_chunks() {
+        let inputs: Vec<Fr> = (1..=17u64).map(Fr::from).collect();
+        let first = poseidon(&inputs[..16]).unwrap();
+        let last = poseidon(&inputs[16..]).unwrap();
+        assert_eq!(poseidon_modular(&inputs).unwrap(), poseidon(&[first, last]).unwrap());
+        assert_eq!(poseidon_modular(&inputs[..3]).unwrap(), poseidon(&inputs[..3]).unwrap());
+        assert!(poseidon_modular(&[]).is_err());
+    }
+}
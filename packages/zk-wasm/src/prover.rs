@@ -0,0 +1,234 @@
+// ============================================================================
+// Groth16 proving over circom R1CS
+// ============================================================================
+
+use crate::circom::{LinearCombination, R1CS};
+use crate::snarkjs::{field_to_decimal, g1_to_json, g2_to_json};
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::r1cs_to_qap::{LibsnarkReduction, R1CSToQAP};
+use ark_groth16::{Groth16, Proof, ProvingKey};
+use ark_poly::EvaluationDomain;
+use ark_relations::lc;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable,
+};
+use ark_snark::SNARK;
+use ark_std::UniformRand;
+use rand::rngs::OsRng;
+
+/// Circom R1CS wrapped as an arkworks circuit.
+///
+/// Without a witness it can only be used for key generation.
+#[derive(Clone)]
+pub struct R1CSCircuit<'a> {
+    pub r1cs: &'a R1CS,
+    pub witness: Option<&'a [Fr]>,
+}
+
+impl ConstraintSynthesizer<Fr> for R1CSCircuit<'_> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let num_instance = self.r1cs.num_instance();
+        let value = |i: usize| {
+            self.witness
+                .map(|w| w[i])
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        // Index 0 is the constant `1` that arkworks already allocates
+        for i in 1..num_instance {
+            cs.new_input_variable(|| value(i))?;
+        }
+        for i in num_instance..self.r1cs.num_wires {
+            cs.new_witness_variable(|| value(i))?;
+        }
+
+        let variable = |index: usize| {
+            if index < num_instance {
+                Variable::Instance(index)
+            } else {
+                Variable::Witness(index - num_instance)
+            }
+        };
+        let to_lc = |terms: &LinearCombination| {
+            terms
+                .iter()
+                .fold(lc!(), |acc, (index, coeff)| acc + (*coeff, variable(*index)))
+        };
+
+        for (a, b, c) in &self.r1cs.constraints {
+            cs.enforce_constraint(to_lc(a), to_lc(b), to_lc(c))?;
+        }
+        Ok(())
+    }
+}
+
+/// Create a Groth16 proof for a circom R1CS and its full witness
+pub fn prove(pk: &ProvingKey<Bn254>, r1cs: &R1CS, witness: &[Fr]) -> Result<Proof<Bn254>, String> {
+    if witness.len() != r1cs.num_wires {
+        return Err(format!(
+            "Witness has {} values but the circuit has {} wires",
+            witness.len(),
+            r1cs.num_wires
+        ));
+    }
+    let circuit = R1CSCircuit {
+        r1cs,
+        witness: Some(witness),
+    };
+    Groth16::<Bn254>::prove(pk, circuit, &mut OsRng)
+        .map_err(|e| format!("Proof generation failed: {:?}", e))
+}
+
+/// Create a Groth16 proof for a snarkjs proving key and its constraint matrices.
+///
+/// `witness` is the full circom witness, starting with the constant `1`.
+pub fn prove_with_matrices(
+    pk: &ProvingKey<Bn254>,
+    matrices: &ConstraintMatrices<Fr>,
+    witness: &[Fr],
+) -> Result<Proof<Bn254>, String> {
+    let num_vars = matrices.num_instance_variables + matrices.num_witness_variables;
+    if witness.len() != num_vars {
+        return Err(format!(
+            "Witness has {} values but the proving key expects {}",
+            witness.len(),
+            num_vars
+        ));
+    }
+    let r = Fr::rand(&mut OsRng);
+    let s = Fr::rand(&mut OsRng);
+    Groth16::<Bn254, CircomReduction>::create_proof_with_reduction_and_matrices(
+        pk,
+        r,
+        s,
+        matrices,
+        matrices.num_instance_variables,
+        matrices.num_constraints,
+        witness,
+    )
+    .map_err(|e| format!("Proof generation failed: {:?}", e))
+}
+
+/// R1CS-to-QAP reduction used by snarkjs.
+///
+/// snarkjs evaluates `A`, `B` and `C` on the odd coset of a domain of twice
+/// the size instead of dividing by the vanishing polynomial, so `.zkey`
+/// H-queries are only compatible with this witness map.
+pub struct CircomReduction;
+
+impl R1CSToQAP for CircomReduction {
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError> {
+        LibsnarkReduction::instance_map_with_evaluation::<F, D>(cs, t)
+    }
+
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> Result<Vec<F>, SynthesisError> {
+        let domain =
+            D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let domain_size = domain.size();
+        let eval = |terms: &[(F, usize)]| {
+            terms
+                .iter()
+                .fold(F::zero(), |acc, (coeff, index)| acc + *coeff * full_assignment[*index])
+        };
+
+        let mut a = vec![F::zero(); domain_size];
+        let mut b = vec![F::zero(); domain_size];
+        for (i, (at, bt)) in matrices.a.iter().zip(&matrices.b).enumerate().take(num_constraints) {
+            a[i] = eval(at);
+            b[i] = eval(bt);
+        }
+        a[num_constraints..num_constraints + num_inputs]
+            .copy_from_slice(&full_assignment[..num_inputs]);
+
+        let mut c = vec![F::zero(); domain_size];
+        for i in 0..num_constraints {
+            c[i] = a[i] * b[i];
+        }
+
+        // Generator of the coset: a primitive root of unity of order 2n
+        let root_of_unity = D::new(2 * domain_size)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?
+            .element(1);
+
+        for evals in [&mut a, &mut b, &mut c] {
+            domain.ifft_in_place(evals);
+            D::distribute_powers_and_mul_by_const(evals, root_of_unity, F::one());
+            domain.fft_in_place(evals);
+        }
+
+        let mut h = domain.mul_polynomials_in_evaluation_domain(&a, &b);
+        for (h_i, c_i) in h.iter_mut().zip(c) {
+            *h_i -= c_i;
+        }
+        Ok(h)
+    }
+
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        _: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        // Lagrange coefficients over the odd powers of the 2n domain
+        let mut scalars: Vec<F> = (0..2 * max_power + 1)
+            .map(|i| delta_inverse * t.pow([i as u64]))
+            .collect();
+        let domain = D::new(scalars.len()).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        domain.ifft_in_place(&mut scalars);
+        Ok(scalars.into_iter().skip(1).step_by(2).collect())
+    }
+}
+
+/// Public signals (outputs followed by public inputs) of a full witness
+pub fn public_signals(r1cs: &R1CS, witness: &[Fr]) -> Vec<Fr> {
+    witness[1..r1cs.num_instance()].to_vec()
+}
+
+/// Render a proof and its public signals in snarkjs' `{ proof, publicSignals }` layout
+pub fn proof_to_snarkjs_json(proof: &Proof<Bn254>, public_signals: &[Fr]) -> serde_json::Value {
+    serde_json::json!({
+        "proof": {
+            "pi_a": g1_to_json(&proof.a),
+            "pi_b": g2_to_json(&proof.b),
+            "pi_c": g1_to_json(&proof.c),
+            "protocol": "groth16",
+            "curve": "bn128"
+        },
+        "publicSignals": public_signals.iter().map(field_to_decimal).collect::<Vec<_>>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circom::{parse_r1cs, tests::multiplier_r1cs};
+
+    #[test]
+    fn test_prove_and_verify_multiplier() {
+        let r1cs = parse_r1cs(&multiplier_r1cs()).unwrap();
+        let setup = R1CSCircuit { r1cs: &r1cs, witness: None };
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(setup, &mut OsRng).unwrap();
+
+        let witness: Vec<Fr> = [1u64, 33, 3, 11].iter().map(|v| Fr::from(*v)).collect();
+        let proof = prove(&pk, &r1cs, &witness).unwrap();
+        let signals = public_signals(&r1cs, &witness);
+        assert_eq!(signals, vec![Fr::from(33u64)]);
+        assert!(Groth16::<Bn254>::verify(&vk, &signals, &proof).unwrap());
+        assert!(!Groth16::<Bn254>::verify(&vk, &[Fr::from(34u64)], &proof).unwrap());
+
+        let json = proof_to_snarkjs_json(&proof, &signals);
+        assert_eq!(json["publicSignals"], serde_json::json!(["33"]));
+        assert_eq!(json["proof"]["pi_a"][2], "1");
+        assert_eq!(json["proof"]["pi_b"][2], serde_json::json!(["1", "0"]));
+    }
+}
@@ -0,0 +1,307 @@
+// ============================================================================
+// RSA public keys and PKCS#1 v1.5 signature checking
+// ============================================================================
+
+use num_bigint::BigUint;
+use std::fmt;
+
+/// DER `DigestInfo` prefix for SHA-256 (RFC 8017 §9.2, note 1)
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+    0x05, 0x00, 0x04, 0x20,
+];
+
+/// RSA public key `(n, e)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    pub modulus: BigUint,
+    pub exponent: BigUint,
+}
+
+impl RsaPublicKey {
+    /// Key with the usual public exponent 65537
+    pub fn from_modulus(modulus: BigUint) -> Self {
+        Self {
+            modulus,
+            exponent: BigUint::from(65537u32),
+        }
+    }
+
+    /// Modulus length in bytes (`k` in RFC 8017)
+    pub fn size(&self) -> usize {
+        self.modulus.bits().div_ceil(8) as usize
+    }
+
+    /// Decode a DER `SubjectPublicKeyInfo` (as in DKIM `p=`) or a bare
+    /// PKCS#1 `RSAPublicKey`
+    pub fn from_der(der: &[u8]) -> Result<Self, String> {
+        let (tag, content, rest) = der_element(der)?;
+        if tag != DER_SEQUENCE || !rest.is_empty() {
+            return Err("Expected a single DER SEQUENCE".to_string());
+        }
+
+        let (first_tag, _, _) = der_element(content)?;
+        if first_tag == DER_INTEGER {
+            return Self::from_pkcs1_sequence(content);
+        }
+
+        // SubjectPublicKeyInfo ::= SEQUENCE { algorithm AlgorithmIdentifier, subjectPublicKey BIT STRING }
+        let (algorithm_tag, algorithm, rest) = der_element(content)?;
+        if algorithm_tag != DER_SEQUENCE {
+            return Err("Expected an AlgorithmIdentifier SEQUENCE".to_string());
+        }
+        let (oid_tag, oid, _) = der_element(algorithm)?;
+        if oid_tag != DER_OID || oid != RSA_ENCRYPTION_OID {
+            return Err("Public key algorithm is not rsaEncryption".to_string());
+        }
+        let (bits_tag, bits, rest) = der_element(rest)?;
+        if bits_tag != DER_BIT_STRING || !rest.is_empty() {
+            return Err("Expected the subjectPublicKey BIT STRING".to_string());
+        }
+        match bits.split_first() {
+            Some((0, key)) => {
+                let (tag, content, rest) = der_element(key)?;
+                if tag != DER_SEQUENCE || !rest.is_empty() {
+                    return Err("Expected an RSAPublicKey SEQUENCE".to_string());
+                }
+                Self::from_pkcs1_sequence(content)
+            }
+            _ => Err("subjectPublicKey has unused bits".to_string()),
+        }
+    }
+
+    /// `RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }`
+    fn from_pkcs1_sequence(content: &[u8]) -> Result<Self, String> {
+        let (n_tag, n, rest) = der_element(content)?;
+        let (e_tag, e, rest) = der_element(rest)?;
+        if n_tag != DER_INTEGER || e_tag != DER_INTEGER || !rest.is_empty() {
+            return Err("RSAPublicKey must hold exactly two INTEGERs".to_string());
+        }
+        if n.first().is_some_and(|b| b & 0x80 != 0) || e.first().is_some_and(|b| b & 0x80 != 0) {
+            return Err("RSA modulus and exponent must be positive".to_string());
+        }
+        let exponent = BigUint::from_bytes_be(e);
+        if exponent < BigUint::from(3u32) || !exponent.bit(0) {
+            return Err(format!("RSA public exponent {} must be odd and at least 3", exponent));
+        }
+        Ok(Self {
+            modulus: BigUint::from_bytes_be(n),
+            exponent,
+        })
+    }
+}
+
+const DER_INTEGER: u8 = 0x02;
+const DER_BIT_STRING: u8 = 0x03;
+const DER_OID: u8 = 0x06;
+const DER_SEQUENCE: u8 = 0x30;
+/// 1.2.840.113549.1.1.1
+const RSA_ENCRYPTION_OID: [u8; 9] = [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+/// Split one DER TLV off `data`: `(tag, content, rest)`
+fn der_element(data: &[u8]) -> Result<(u8, &[u8], &[u8]), String> {
+    let truncated = || "Truncated DER element".to_string();
+    let (&tag, data) = data.split_first().ok_or_else(truncated)?;
+    let (&first, data) = data.split_first().ok_or_else(truncated)?;
+
+    let (length, data) = if first < 0x80 {
+        (first as usize, data)
+    } else {
+        let num_bytes = (first & 0x7f) as usize;
+        if num_bytes == 0 || num_bytes > 4 || data.len() < num_bytes {
+            return Err("Unsupported DER length encoding".to_string());
+        }
+        let length = data[..num_bytes]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (length, &data[num_bytes..])
+    };
+
+    if data.len() < length {
+        return Err(truncated());
+    }
+    Ok((tag, &data[..length], &data[length..]))
+}
+
+/// Why a PKCS#1 v1.5 signature was rejected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RsaError {
+    /// The modulus is too small to be trusted (RFC 8301 requires 1024 bits)
+    KeyTooSmall(u64),
+    /// The signature is longer than the modulus or not smaller than it
+    SignatureOutOfRange,
+    /// `s^e mod n` is not `00 01 FF.. 00 || DigestInfo || H`: wrong key or corrupted signature
+    InvalidPadding,
+    /// The signature was made over a digest other than SHA-256
+    UnexpectedDigestAlgorithm,
+    /// The signed digest differs from the digest of the data
+    DigestMismatch,
+}
+
+impl fmt::Display for RsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeyTooSmall(bits) => write!(f, "RSA key is only {} bits (minimum 1024)", bits),
+            Self::SignatureOutOfRange => write!(f, "Signature is not smaller than the RSA modulus"),
+            Self::InvalidPadding => write!(
+                f,
+                "Signature does not decrypt to a PKCS#1 v1.5 block; the key is wrong or b= is corrupted"
+            ),
+            Self::UnexpectedDigestAlgorithm => {
+                write!(f, "Signature does not contain a SHA-256 DigestInfo")
+            }
+            Self::DigestMismatch => write!(f, "Signed digest does not match the computed digest"),
+        }
+    }
+}
+
+impl std::error::Error for RsaError {}
+
+/// RSASSA-PKCS1-v1_5 verification of a SHA-256 `digest` (RFC 8017 §8.2.2)
+pub fn verify_pkcs1v15_sha256(
+    key: &RsaPublicKey,
+    digest: &[u8; 32],
+    signature: &[u8],
+) -> Result<(), RsaError> {
+    let bits = key.modulus.bits();
+    if bits < 1024 {
+        return Err(RsaError::KeyTooSmall(bits));
+    }
+    let k = key.size();
+    if signature.len() > k {
+        return Err(RsaError::SignatureOutOfRange);
+    }
+    let s = BigUint::from_bytes_be(signature);
+    if s >= key.modulus {
+        return Err(RsaError::SignatureOutOfRange);
+    }
+
+    let m = s.modpow(&key.exponent, &key.modulus).to_bytes_be();
+    if m.len() > k - 1 {
+        return Err(RsaError::InvalidPadding);
+    }
+    // I2OSP drops the leading zero byte, so `m` starts at 0x01
+    let mut em = vec![0u8; k - m.len()];
+    em.extend_from_slice(&m);
+
+    let t_len = SHA256_DIGEST_INFO.len() + digest.len();
+    let ps_end = k - t_len - 1;
+    if em[0] != 0x00
+        || em[1] != 0x01
+        || em[2..ps_end].iter().any(|&b| b != 0xff)
+        || em[ps_end] != 0x00
+    {
+        return Err(RsaError::InvalidPadding);
+    }
+    let (digest_info, signed_digest) = em[ps_end + 1..].split_at(SHA256_DIGEST_INFO.len());
+    if digest_info != SHA256_DIGEST_INFO {
+        return Err(RsaError::UnexpectedDigestAlgorithm);
+    }
+    if signed_digest != digest {
+        return Err(RsaError::DigestMismatch);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::sha256;
+
+    /// 2048-bit test key pair: `(public key, private exponent)`
+    pub(crate) fn test_key() -> (RsaPublicKey, BigUint) {
+        let json: serde_json::Value =
+            serde_json::from_str(include_str!("../testdata/dkim_test_key.json")).unwrap();
+        let hex = |name: &str| {
+            BigUint::parse_bytes(json[name].as_str().unwrap().as_bytes(), 16).unwrap()
+        };
+        let key = RsaPublicKey {
+            modulus: hex("n"),
+            exponent: hex("e"),
+        };
+        (key, hex("d"))
+    }
+
+    /// PKCS#1 v1.5 SHA-256 signature with the test key
+    pub(crate) fn sign(data: &[u8]) -> Vec<u8> {
+        let (key, d) = test_key();
+        let k = key.size();
+        let mut em = vec![0x00, 0x01];
+        em.resize(k - SHA256_DIGEST_INFO.len() - 32 - 1, 0xff);
+        em.push(0x00);
+        em.extend_from_slice(&SHA256_DIGEST_INFO);
+        em.extend_from_slice(&sha256(data));
+
+        let s = BigUint::from_bytes_be(&em).modpow(&d, &key.modulus).to_bytes_be();
+        let mut signature = vec![0u8; k - s.len()];
+        signature.extend_from_slice(&s);
+        signature
+    }
+
+    #[test]
+    fn test_decode_der_public_keys() {
+        use base64::{engine::general_purpose, Engine as _};
+        let record = include_str!("../testdata/dkim_test_record.txt");
+        let p = record.trim().rsplit_once("p=").unwrap().1;
+        let spki = general_purpose::STANDARD.decode(p).unwrap();
+
+        let (key, _) = test_key();
+        assert_eq!(RsaPublicKey::from_der(&spki).unwrap(), key);
+        // The BIT STRING of a 2048-bit SubjectPublicKeyInfo starts after 24 bytes
+        assert_eq!(RsaPublicKey::from_der(&spki[24..]).unwrap(), key);
+
+        assert!(RsaPublicKey::from_der(&spki[..100]).is_err());
+        let mut wrong_oid = spki.clone();
+        wrong_oid[14] ^= 1;
+        assert!(RsaPublicKey::from_der(&wrong_oid).unwrap_err().contains("rsaEncryption"));
+
+        // The RSAPublicKey ends with the exponent 65537 as `02 03 01 00 01`
+        let pkcs1 = &spki[24..];
+        assert_eq!(&pkcs1[pkcs1.len() - 5..], [0x02, 0x03, 0x01, 0x00, 0x01]);
+        let with_exponent = |e: [u8; 3]| {
+            let mut der = pkcs1.to_vec();
+            let len = der.len();
+            der[len - 3..].copy_from_slice(&e);
+            RsaPublicKey::from_der(&der)
+        };
+        assert_eq!(with_exponent([0, 0, 3]).unwrap().exponent, BigUint::from(3u32));
+        for e in [[0, 0, 0], [0, 0, 1], [0, 0, 2], [1, 0, 0]] {
+            let err = with_exponent(e).unwrap_err();
+            assert!(err.contains("must be odd and at least 3"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_verify_pkcs1v15() {
+        let (key, _) = test_key();
+        assert_eq!(key.size(), 256);
+        let signature = sign(b"hello");
+        assert_eq!(verify_pkcs1v15_sha256(&key, &sha256(b"hello"), &signature), Ok(()));
+        assert_eq!(
+            verify_pkcs1v15_sha256(&key, &sha256(b"hellO"), &signature),
+            Err(RsaError::DigestMismatch)
+        );
+
+        let mut corrupted = signature.clone();
+        corrupted[100] ^= 1;
+        assert_eq!(
+            verify_pkcs1v15_sha256(&key, &sha256(b"hello"), &corrupted),
+            Err(RsaError::InvalidPadding)
+        );
+
+        let other = RsaPublicKey::from_modulus(&key.modulus - 2u32);
+        assert_eq!(
+            verify_pkcs1v15_sha256(&other, &sha256(b"hello"), &signature),
+            Err(RsaError::InvalidPadding)
+        );
+        assert_eq!(
+            verify_pkcs1v15_sha256(&key, &sha256(b"hello"), &[0xff; 256]),
+            Err(RsaError::SignatureOutOfRange)
+        );
+        let small = RsaPublicKey::from_modulus(BigUint::from(3233u32));
+        assert_eq!(
+            verify_pkcs1v15_sha256(&small, &sha256(b"hello"), &[1]),
+            Err(RsaError::KeyTooSmall(12))
+        );
+    }
+}
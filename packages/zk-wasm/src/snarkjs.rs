@@ -0,0 +1,285 @@
+// ============================================================================
+// snarkjs JSON encoding of BN254 keys and points
+// ============================================================================
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{One, PrimeField, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use num_bigint::BigUint;
+use serde_json::Value;
+
+/// Decimal string of a prime field element
+pub fn field_to_decimal<F: PrimeField>(value: &F) -> String {
+    let value: BigUint = value.into_bigint().into();
+    value.to_string()
+}
+
+/// Parse a canonical decimal field element, rejecting values `>=` the modulus
+pub fn field_from_decimal<F: PrimeField>(value: &Value) -> Result<F, String> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| format!("Expected a decimal string, got {}", value))?;
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("'{}' is not a decimal integer", s));
+    }
+    let n = BigUint::parse_bytes(s.as_bytes(), 10)
+        .ok_or_else(|| format!("'{}' is not a decimal integer", s))?;
+    let modulus: BigUint = F::MODULUS.into();
+    if n >= modulus {
+        return Err(format!("'{}' is not smaller than the field modulus", s));
+    }
+    Ok(F::from(n))
+}
+
+/// Projective `[x, y, z]` coordinates as snarkjs writes them
+pub fn g1_to_json(point: &G1Affine) -> Value {
+    if point.infinity {
+        return serde_json::json!(["0", "1", "0"]);
+    }
+    serde_json::json!([
+        field_to_decimal(&point.x),
+        field_to_decimal(&point.y),
+        field_to_decimal(&Fq::one())
+    ])
+}
+
+pub fn g2_to_json(point: &G2Affine) -> Value {
+    if point.infinity {
+        return serde_json::json!([fq2_to_json(&Fq2::zero()), fq2_to_json(&Fq2::one()), fq2_to_json(&Fq2::zero())]);
+    }
+    serde_json::json!([fq2_to_json(&point.x), fq2_to_json(&point.y), fq2_to_json(&Fq2::one())])
+}
+
+fn fq2_to_json(value: &Fq2) -> Value {
+    serde_json::json!([field_to_decimal(&value.c0), field_to_decimal(&value.c1)])
+}
+
+/// Parse a snarkjs G1 point, checking that it lies on the curve
+pub fn g1_from_json(value: &Value) -> Result<G1Affine, String> {
+    let coords = coordinates(value, "G1")?;
+    let x: Fq = field_from_decimal(&coords[0])?;
+    let y: Fq = field_from_decimal(&coords[1])?;
+    let z: Fq = match coords.get(2) {
+        Some(z) => field_from_decimal(z)?,
+        None => Fq::one(),
+    };
+
+    if z.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+    if !z.is_one() {
+        return Err("G1 point is not in affine form (z != 1)".to_string());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err("G1 point is not on the curve".to_string());
+    }
+    // BN254 G1 has cofactor 1, so every curve point is in the subgroup
+    Ok(point)
+}
+
+/// Parse a snarkjs G2 point, checking curve and subgroup membership
+pub fn g2_from_json(value: &Value) -> Result<G2Affine, String> {
+    let coords = coordinates(value, "G2")?;
+    let x = fq2_from_json(&coords[0])?;
+    let y = fq2_from_json(&coords[1])?;
+    let z = match coords.get(2) {
+        Some(z) => fq2_from_json(z)?,
+        None => Fq2::one(),
+    };
+
+    if z.is_zero() {
+        return Ok(G2Affine::identity());
+    }
+    if !z.is_one() {
+        return Err("G2 point is not in affine form (z != 1)".to_string());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err("G2 point is not on the curve".to_string());
+    }
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("G2 point is not in the prime-order subgroup".to_string());
+    }
+    Ok(point)
+}
+
+fn fq2_from_json(value: &Value) -> Result<Fq2, String> {
+    match value.as_array().map(Vec::as_slice) {
+        Some([c0, c1]) => Ok(Fq2::new(field_from_decimal(c0)?, field_from_decimal(c1)?)),
+        _ => Err(format!("Expected an [c0, c1] pair, got {}", value)),
+    }
+}
+
+fn coordinates<'a>(value: &'a Value, group: &str) -> Result<&'a [Value], String> {
+    match value.as_array() {
+        Some(coords) if coords.len() == 2 || coords.len() == 3 => Ok(coords),
+        _ => Err(format!("Expected {} point coordinates [x, y, z], got {}", group, value)),
+    }
+}
+
+/// Parse a snarkjs Groth16 `verification_key.json`
+pub fn verifying_key_from_json(json: &str) -> Result<VerifyingKey<Bn254>, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid verification key JSON: {}", e))?;
+
+    if let Some(protocol) = value.get("protocol").and_then(Value::as_str) {
+        if protocol != "groth16" {
+            return Err(format!("Unsupported protocol '{}' (expected groth16)", protocol));
+        }
+    }
+    if let Some(curve) = value.get("curve").and_then(Value::as_str) {
+        if curve != "bn128" && curve != "bn254" {
+            return Err(format!("Unsupported curve '{}' (expected bn128)", curve));
+        }
+    }
+
+    let field = |name: &str| {
+        value
+            .get(name)
+            .ok_or_else(|| format!("Verification key is missing '{}'", name))
+    };
+    let with_name = |name: &str, e: String| format!("Invalid '{}': {}", name, e);
+
+    let alpha_g1 = g1_from_json(field("vk_alpha_1")?).map_err(|e| with_name("vk_alpha_1", e))?;
+    let beta_g2 = g2_from_json(field("vk_beta_2")?).map_err(|e| with_name("vk_beta_2", e))?;
+    let gamma_g2 = g2_from_json(field("vk_gamma_2")?).map_err(|e| with_name("vk_gamma_2", e))?;
+    let delta_g2 = g2_from_json(field("vk_delta_2")?).map_err(|e| with_name("vk_delta_2", e))?;
+    let gamma_abc_g1 = field("IC")?
+        .as_array()
+        .ok_or_else(|| "'IC' must be an array".to_string())?
+        .iter()
+        .enumerate()
+        .map(|(i, p)| g1_from_json(p).map_err(|e| with_name(&format!("IC[{}]", i), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if gamma_abc_g1.is_empty() {
+        return Err("'IC' must contain at least one point".to_string());
+    }
+    if let Some(n_public) = value.get("nPublic").and_then(Value::as_u64) {
+        if n_public as usize + 1 != gamma_abc_g1.len() {
+            return Err(format!(
+                "'nPublic' is {} but 'IC' has {} points",
+                n_public,
+                gamma_abc_g1.len()
+            ));
+        }
+    }
+
+    Ok(VerifyingKey {
+        alpha_g1,
+        beta_g2,
+        gamma_g2,
+        delta_g2,
+        gamma_abc_g1,
+    })
+}
+
+/// Render a verifying key as snarkjs `verification_key.json`
+pub fn verifying_key_to_json(vk: &VerifyingKey<Bn254>) -> Value {
+    serde_json::json!({
+        "protocol": "groth16",
+        "curve": "bn128",
+        "nPublic": vk.gamma_abc_g1.len() - 1,
+        "vk_alpha_1": g1_to_json(&vk.alpha_g1),
+        "vk_beta_2": g2_to_json(&vk.beta_g2),
+        "vk_gamma_2": g2_to_json(&vk.gamma_g2),
+        "vk_delta_2": g2_to_json(&vk.delta_g2),
+        "IC": vk.gamma_abc_g1.iter().map(g1_to_json).collect::<Vec<_>>()
+    })
+}
+
+/// Parse a snarkjs `{ pi_a, pi_b, pi_c }` proof object
+pub fn proof_from_json(value: &Value) -> Result<Proof<Bn254>, String> {
+    if let Some(protocol) = value.get("protocol").and_then(Value::as_str) {
+        if protocol != "groth16" {
+            return Err(format!("Unsupported protocol '{}' (expected groth16)", protocol));
+        }
+    }
+    let field = |name: &str| {
+        value
+            .get(name)
+            .ok_or_else(|| format!("Proof is missing '{}'", name))
+    };
+
+    Ok(Proof {
+        a: g1_from_json(field("pi_a")?).map_err(|e| format!("Invalid 'pi_a': {}", e))?,
+        b: g2_from_json(field("pi_b")?).map_err(|e| format!("Invalid 'pi_b': {}", e))?,
+        c: g1_from_json(field("pi_c")?).map_err(|e| format!("Invalid 'pi_c': {}", e))?,
+    })
+}
+
+/// Parse a snarkjs `publicSignals` array of decimal strings.
+///
+/// Signals must already be reduced: values `>=` the BN254 scalar modulus are
+/// rejected rather than silently wrapped.
+pub fn public_signals_from_json(value: &Value) -> Result<Vec<Fr>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| "Public signals must be an array".to_string())?
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            field_from_decimal(s).map_err(|e| format!("Invalid public signal {}: {}", i, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_circuit_verification_key() {
+        let vk = verifying_key_from_json(include_str!("../testdata/verification_key.json")).unwrap();
+        assert_eq!(vk.gamma_abc_g1.len(), 20);
+
+        let roundtrip = verifying_key_to_json(&vk).to_string();
+        assert_eq!(verifying_key_from_json(&roundtrip).unwrap(), vk);
+    }
+
+    #[test]
+    fn test_rejects_invalid_points() {
+        let mut vk: Value =
+            serde_json::from_str(include_str!("../testdata/verification_key.json")).unwrap();
+        vk["vk_alpha_1"][1] = Value::from("1");
+        let err = verifying_key_from_json(&vk.to_string()).unwrap_err();
+        assert!(err.contains("not on the curve"), "{}", err);
+
+        // A point on the twist that is outside the prime-order subgroup
+        let mut vk: Value =
+            serde_json::from_str(include_str!("../testdata/verification_key.json")).unwrap();
+        vk["vk_beta_2"] = g2_to_json(&non_subgroup_g2());
+        let err = verifying_key_from_json(&vk.to_string()).unwrap_err();
+        assert!(err.contains("subgroup"), "{}", err);
+
+        let modulus: BigUint = Fq::MODULUS.into();
+        let mut vk: Value =
+            serde_json::from_str(include_str!("../testdata/verification_key.json")).unwrap();
+        vk["IC"][0][0] = Value::from(modulus.to_string());
+        assert!(verifying_key_from_json(&vk.to_string()).is_err());
+    }
+
+    /// Search small x-coordinates for a twist point outside the subgroup
+    fn non_subgroup_g2() -> G2Affine {
+        (1u64..)
+            .find_map(|i| {
+                G2Affine::get_point_from_x_unchecked(Fq2::new(Fq::from(i), Fq::zero()), false)
+                    .filter(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_public_signals_reject_unreduced_values() {
+        let modulus: BigUint = Fr::MODULUS.into();
+        let below = (&modulus - 1u32).to_string();
+        let signals = public_signals_from_json(&serde_json::json!(["7", below])).unwrap();
+        assert_eq!(signals[0], Fr::from(7u64));
+        assert_eq!(signals[1], -Fr::one());
+
+        assert!(public_signals_from_json(&serde_json::json!([modulus.to_string()])).is_err());
+        assert!(public_signals_from_json(&serde_json::json!(["0x10"])).is_err());
+        assert!(public_signals_from_json(&serde_json::json!(["-1"])).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+// ============================================================================
+// Batched Groth16 verification
+// ============================================================================
+
+use crate::error::ZkWasmError;
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_groth16::{PreparedVerifyingKey, Proof};
+use ark_std::UniformRand;
+use rand::rngs::OsRng;
+
+/// Verify many proofs against one key with a single multi-pairing.
+///
+/// Each proof's equation `e(A, B) = e(alpha, beta) * e(L, gamma) * e(C, delta)`
+/// is scaled by a random `r_i` and the results are multiplied together, so the
+/// batch passes only if every proof is valid (except with negligible
+/// probability). A failing batch does not say which proof is bad.
+pub fn verify_batch(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    batch: &[(Proof<Bn254>, Vec<Fr>)],
+) -> Result<bool, ZkWasmError> {
+    let num_public = pvk
+        .vk
+        .gamma_abc_g1
+        .len()
+        .checked_sub(1)
+        .ok_or_else(|| {
+            ZkWasmError::MalformedVerifyingKey("Verifying key has no IC points".to_string())
+        })?;
+    if let Some(i) = batch.iter().position(|(_, inputs)| inputs.len() != num_public) {
+        return Err(ZkWasmError::MalformedPublicSignals(format!(
+            "Proof {} has {} public signals, expected {}",
+            i,
+            batch[i].1.len(),
+            num_public
+        )));
+    }
+    if batch.is_empty() {
+        return Ok(true);
+    }
+
+    let mut rng = OsRng;
+    let randomizers: Vec<Fr> = (0..batch.len()).map(|_| Fr::rand(&mut rng)).collect();
+
+    // sum_i r_i * L_i = (sum_i r_i) * IC_0 + sum_j (sum_i r_i * x_ij) * IC_j
+    let r_sum: Fr = randomizers.iter().sum();
+    let mut input_scalars = vec![Fr::zero(); num_public];
+    for (r, (_, inputs)) in randomizers.iter().zip(batch) {
+        for (acc, x) in input_scalars.iter_mut().zip(inputs) {
+            *acc += *r * x;
+        }
+    }
+    let combined_inputs = G1Projective::msm(&pvk.vk.gamma_abc_g1[1..], &input_scalars)
+        .map_err(|_| {
+            ZkWasmError::MalformedPublicSignals("Public input length mismatch".to_string())
+        })?
+        + pvk.vk.gamma_abc_g1[0] * r_sum;
+
+    let proofs_a: Vec<_> = randomizers
+        .iter()
+        .zip(batch)
+        .map(|(r, (proof, _))| proof.a * r)
+        .collect();
+    let proofs_c: Vec<_> = batch.iter().map(|(proof, _)| proof.c).collect();
+    let combined_c = G1Projective::msm(&proofs_c, &randomizers)
+        .map_err(|_| ZkWasmError::InvalidInput("Proof count mismatch".to_string()))?;
+
+    let mut g1 = G1Projective::normalize_batch(&proofs_a);
+    let mut g2: Vec<<Bn254 as Pairing>::G2Prepared> =
+        batch.iter().map(|(proof, _)| proof.b.into()).collect();
+    g1.push(combined_inputs.into_affine());
+    g2.push(pvk.gamma_g2_neg_pc.clone());
+    g1.push(combined_c.into_affine());
+    g2.push(pvk.delta_g2_neg_pc.clone());
+
+    let miller_loop = Bn254::multi_miller_loop(g1, g2);
+    let product = Bn254::final_exponentiation(miller_loop)
+        .ok_or_else(|| ZkWasmError::Verification("Final exponentiation failed".to_string()))?;
+
+    Ok(product.0 == pvk.alpha_g1_beta_g2.pow(r_sum.into_bigint()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circom::{parse_r1cs, tests::multiplier_r1cs};
+    use crate::prover::{prove, R1CSCircuit};
+    use ark_groth16::Groth16;
+    use ark_snark::SNARK;
+
+    #[test]
+    fn test_verify_batch() {
+        let r1cs = parse_r1cs(&multiplier_r1cs()).unwrap();
+        let setup = R1CSCircuit { r1cs: &r1cs, witness: None };
+        let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(setup, &mut OsRng).unwrap();
+        let pvk = Groth16::<Bn254>::process_vk(&vk).unwrap();
+
+        let mut batch: Vec<_> = [(2u64, 3u64), (4, 5), (6, 7)]
+            .iter()
+            .map(|(a, b)| {
+                let witness: Vec<Fr> =
+                    [1, a * b, *a, *b].iter().map(|v| Fr::from(*v)).collect();
+                (prove(&pk, &r1cs, &witness).unwrap(), vec![Fr::from(a * b)])
+            })
+            .collect();
+        assert!(verify_batch(&pvk, &batch).unwrap());
+        assert!(verify_batch(&pvk, &[]).unwrap());
+
+        // A single wrong public signal fails the whole batch
+        batch[1].1 = vec![Fr::from(42u64)];
+        assert!(!verify_batch(&pvk, &batch).unwrap());
+
+        batch[1].1 = vec![];
+        let err = verify_batch(&pvk, &batch).unwrap_err();
+        assert_eq!(err.code(), "MALFORMED_PUBLIC_SIGNALS");
+
+        let mut no_ic = pvk.clone();
+        no_ic.vk.gamma_abc_g1.clear();
+        let err = verify_batch(&no_ic, &batch).unwrap_err();
+        assert_eq!(err.code(), "MALFORMED_VERIFYING_KEY");
+    }
+}
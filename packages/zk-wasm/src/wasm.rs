@@ -0,0 +1,93 @@
+// ============================================================================
+// JavaScript-only bindings (`wasm` feature)
+// ============================================================================
+//
+// Everything else is exported straight from the core types with
+// `cfg_attr(feature = "wasm", wasm_bindgen)`; this module holds what only
+// makes sense in JavaScript: JS values in and out, the panic hook and the
+// conversion of errors to JS `Error` objects.
+
+use crate::error::ZkWasmError;
+use crate::{ZKWASMProver, ZKWASMVerifier};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(start)]
+pub fn init() {
+    console_error_panic_hook::set_once();
+}
+
+/// A JS `Error` named "ZkWasmError" with `code` and `details` properties
+impl From<ZkWasmError> for JsValue {
+    fn from(e: ZkWasmError) -> Self {
+        let error = js_sys::Error::new(&e.to_string());
+        error.set_name("ZkWasmError");
+        let details = e
+            .details()
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap_or(JsValue::UNDEFINED);
+        // Setting a property on a fresh Error object cannot fail
+        let _ = js_sys::Reflect::set(&error, &"code".into(), &e.code().into());
+        let _ = js_sys::Reflect::set(&error, &"details".into(), &details);
+        error.into()
+    }
+}
+
+#[wasm_bindgen]
+impl ZKWASMVerifier {
+    /// Verify a snarkjs `{ pi_a, pi_b, pi_c }` proof against decimal `publicSignals`
+    pub fn verify_snarkjs_proof(
+        &self,
+        proof: JsValue,
+        public_signals: JsValue,
+    ) -> Result<bool, ZkWasmError> {
+        let proof: serde_json::Value = serde_wasm_bindgen::from_value(proof)
+            .map_err(|e| ZkWasmError::MalformedProof(e.to_string()))?;
+        let public_signals: serde_json::Value = serde_wasm_bindgen::from_value(public_signals)
+            .map_err(|e| ZkWasmError::MalformedPublicSignals(e.to_string()))?;
+
+        self.verify_snarkjs(&proof, &public_signals)
+    }
+
+    /// Verify many snarkjs proofs at once with a single multi-pairing.
+    ///
+    /// `proofs` is an array of `{ pi_a, pi_b, pi_c }` objects and
+    /// `public_signals` the matching array of `publicSignals` arrays. Returns
+    /// `false` if any proof is invalid; verify individually to find which.
+    pub fn verify_batch(&self, proofs: JsValue, public_signals: JsValue) -> Result<bool, ZkWasmError> {
+        let proofs: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(proofs)
+            .map_err(|e| ZkWasmError::MalformedProof(e.to_string()))?;
+        let public_signals: Vec<serde_json::Value> = serde_wasm_bindgen::from_value(public_signals)
+            .map_err(|e| ZkWasmError::MalformedPublicSignals(e.to_string()))?;
+
+        self.verify_snarkjs_batch(&proofs, &public_signals)
+    }
+}
+
+#[wasm_bindgen]
+impl ZKWASMProver {
+    /// Calculate the witness for `inputs` and prove it.
+    ///
+    /// Returns the snarkjs `{ proof, publicSignals }` object as a JSON string.
+    pub fn generate_proof(&mut self, inputs: JsValue) -> Result<JsValue, ZkWasmError> {
+        let inputs: serde_json::Value = serde_wasm_bindgen::from_value(inputs)
+            .map_err(|e| ZkWasmError::InvalidInput(format!("Failed to parse inputs: {}", e)))?;
+
+        let proof = self.prove_inputs(&inputs)?;
+        Ok(JsValue::from_str(&proof.to_string()))
+    }
+}
+
+/// One-shot proof generation from circuit artifacts and a proving key
+#[wasm_bindgen]
+pub fn generate_proof(
+    r1cs_bytes: &[u8],
+    wasm_bytes: &[u8],
+    zkey_bytes: &[u8],
+    inputs: JsValue,
+) -> Result<JsValue, ZkWasmError> {
+    let mut prover = ZKWASMProver::new();
+    prover.load_circuit(wasm_bytes, r1cs_bytes)?;
+    prover.load_proving_key(zkey_bytes)?;
+    prover.generate_proof(inputs)
+}
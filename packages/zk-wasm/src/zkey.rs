@@ -0,0 +1,427 @@
+// ============================================================================
+// snarkjs `.zkey` proving key import
+// ============================================================================
+
+use crate::circom::{read_sections, BinReader};
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInt, PrimeField, Zero};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintMatrices;
+use num_bigint::BigUint;
+
+// zkey section types
+const ZKEY_SECTION_HEADER: u32 = 1;
+const ZKEY_SECTION_GROTH16_HEADER: u32 = 2;
+const ZKEY_SECTION_IC: u32 = 3;
+const ZKEY_SECTION_COEFFS: u32 = 4;
+const ZKEY_SECTION_A: u32 = 5;
+const ZKEY_SECTION_B1: u32 = 6;
+const ZKEY_SECTION_B2: u32 = 7;
+const ZKEY_SECTION_C: u32 = 8;
+const ZKEY_SECTION_H: u32 = 9;
+
+/// Protocol id for Groth16 in the zkey header section
+const ZKEY_PROTOCOL_GROTH16: u32 = 1;
+
+/// Field element size in bytes for BN254
+const N8: usize = 32;
+
+/// Fields of the Groth16 header section that the reader needs
+struct Groth16Header {
+    num_vars: usize,
+    num_public: usize,
+    domain_size: usize,
+}
+
+/// Returns true if `data` starts with the snarkjs zkey magic
+pub fn is_zkey(data: &[u8]) -> bool {
+    data.starts_with(b"zkey")
+}
+
+/// Read a snarkjs Groth16 `.zkey` file.
+///
+/// Returns the arkworks proving key and the `A`/`B` constraint matrices that
+/// snarkjs stores alongside it. The matrices exclude the public-input
+/// constraints snarkjs appends, which the circom QAP reduction re-adds.
+pub fn read_zkey(data: &[u8]) -> Result<(ProvingKey<Bn254>, ConstraintMatrices<Fr>), String> {
+    let sections = read_sections(data, b"zkey")?;
+    let section = |ty: u32| {
+        sections
+            .iter()
+            .find(|(t, _)| *t == ty)
+            .map(|(_, s)| BinReader::new(s))
+            .ok_or_else(|| format!("zkey file is missing section {}", ty))
+    };
+
+    let protocol = section(ZKEY_SECTION_HEADER)?.u32()?;
+    if protocol != ZKEY_PROTOCOL_GROTH16 {
+        return Err(format!("Unsupported zkey protocol {} (expected Groth16)", protocol));
+    }
+
+    let mut reader = section(ZKEY_SECTION_GROTH16_HEADER)?;
+    let header = read_groth16_header(&mut reader)?;
+    // H holds one G1 point per domain element, which bounds domain_size
+    // before the coefficient matrices are allocated from it
+    let h_len = sections
+        .iter()
+        .find(|(t, _)| *t == ZKEY_SECTION_H)
+        .map_or(0, |(_, s)| s.len());
+    if !header.domain_size.is_power_of_two()
+        || header.domain_size.checked_mul(2 * N8) != Some(h_len)
+    {
+        return Err(format!(
+            "zkey domain size {} does not match its {}-byte H section",
+            header.domain_size, h_len
+        ));
+    }
+    let alpha_g1 = read_g1(&mut reader)?;
+    let beta_g1 = read_g1(&mut reader)?;
+    let beta_g2 = read_g2(&mut reader)?;
+    let gamma_g2 = read_g2(&mut reader)?;
+    let delta_g1 = read_g1(&mut reader)?;
+    let delta_g2 = read_g2(&mut reader)?;
+
+    let num_private = header.num_vars - header.num_public - 1;
+    let gamma_abc_g1 = read_g1_vec(&mut section(ZKEY_SECTION_IC)?, header.num_public + 1)?;
+    let a_query = read_g1_vec(&mut section(ZKEY_SECTION_A)?, header.num_vars)?;
+    let b_g1_query = read_g1_vec(&mut section(ZKEY_SECTION_B1)?, header.num_vars)?;
+    let b_g2_query = read_g2_vec(&mut section(ZKEY_SECTION_B2)?, header.num_vars)?;
+    let l_query = read_g1_vec(&mut section(ZKEY_SECTION_C)?, num_private)?;
+    let h_query = read_g1_vec(&mut section(ZKEY_SECTION_H)?, header.domain_size)?;
+
+    let pk = ProvingKey {
+        vk: VerifyingKey {
+            alpha_g1,
+            beta_g2,
+            gamma_g2,
+            delta_g2,
+            gamma_abc_g1,
+        },
+        beta_g1,
+        delta_g1,
+        a_query,
+        b_g1_query,
+        b_g2_query,
+        h_query,
+        l_query,
+    };
+
+    let matrices = read_matrices(&mut section(ZKEY_SECTION_COEFFS)?, &header)?;
+    Ok((pk, matrices))
+}
+
+fn read_groth16_header(reader: &mut BinReader) -> Result<Groth16Header, String> {
+    let expect_modulus = |n8: usize, modulus: BigUint, expected: BigUint, name: &str| {
+        if n8 != N8 || modulus != expected {
+            return Err(format!("zkey {} field is not BN254", name));
+        }
+        Ok(())
+    };
+
+    let n8q = reader.u32()? as usize;
+    let q = reader.biguint(n8q)?;
+    expect_modulus(n8q, q, Fq::MODULUS.into(), "base")?;
+    let n8r = reader.u32()? as usize;
+    let r = reader.biguint(n8r)?;
+    expect_modulus(n8r, r, Fr::MODULUS.into(), "scalar")?;
+
+    let header = Groth16Header {
+        num_vars: reader.u32()? as usize,
+        num_public: reader.u32()? as usize,
+        domain_size: reader.u32()? as usize,
+    };
+    if header.num_vars < header.num_public + 1 {
+        return Err("zkey declares more public signals than variables".to_string());
+    }
+    Ok(header)
+}
+
+/// Read the sparse `A`/`B` coefficients (section 4)
+fn read_matrices(
+    reader: &mut BinReader,
+    header: &Groth16Header,
+) -> Result<ConstraintMatrices<Fr>, String> {
+    let num_coeffs = reader.u32()? as usize;
+    let mut matrices = vec![vec![Vec::new(); header.domain_size]; 2];
+    let mut max_constraint = 0;
+
+    for _ in 0..num_coeffs {
+        let matrix = reader.u32()? as usize;
+        let constraint = reader.u32()? as usize;
+        let signal = reader.u32()? as usize;
+        let value = read_coefficient(reader)?;
+
+        if matrix > 1 || constraint >= header.domain_size || signal >= header.num_vars {
+            return Err(format!(
+                "zkey coefficient out of range (matrix {}, constraint {}, signal {})",
+                matrix, constraint, signal
+            ));
+        }
+        max_constraint = max_constraint.max(constraint);
+        matrices[matrix][constraint].push((value, signal));
+    }
+
+    // snarkjs appends one `signal * 0 = 0` constraint per public signal (and
+    // the constant), so the last `num_public + 1` rows are not circuit constraints
+    let num_constraints = (max_constraint + 1)
+        .checked_sub(header.num_public + 1)
+        .ok_or_else(|| "zkey is missing the public input constraints".to_string())?;
+    for matrix in matrices.iter_mut() {
+        matrix.truncate(num_constraints);
+    }
+
+    let b = matrices.pop().unwrap_or_default();
+    let a = matrices.pop().unwrap_or_default();
+    Ok(ConstraintMatrices {
+        num_instance_variables: header.num_public + 1,
+        num_witness_variables: header.num_vars - header.num_public - 1,
+        num_constraints,
+        a_num_non_zero: a.iter().map(Vec::len).sum(),
+        b_num_non_zero: b.iter().map(Vec::len).sum(),
+        c_num_non_zero: 0,
+        a,
+        b,
+        c: Vec::new(),
+    })
+}
+
+fn read_bigint(reader: &mut BinReader) -> Result<BigInt<4>, String> {
+    let bytes = reader.bytes(N8)?;
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(chunk);
+        *limb = u64::from_le_bytes(buf);
+    }
+    Ok(BigInt::new(limbs))
+}
+
+/// Base field element stored in Montgomery form
+fn read_fq(reader: &mut BinReader) -> Result<Fq, String> {
+    let value = read_bigint(reader)?;
+    if value >= Fq::MODULUS {
+        return Err("zkey contains a non-canonical field element".to_string());
+    }
+    Ok(Fq::new_unchecked(value))
+}
+
+/// Matrix coefficients are stored in Montgomery form multiplied by R once more
+fn read_coefficient(reader: &mut BinReader) -> Result<Fr, String> {
+    let value = read_bigint(reader)?;
+    if value >= Fr::MODULUS {
+        return Err("zkey contains a non-canonical coefficient".to_string());
+    }
+    Ok(Fr::new_unchecked(Fr::new_unchecked(value).into_bigint()))
+}
+
+fn read_g1(reader: &mut BinReader) -> Result<G1Affine, String> {
+    let x = read_fq(reader)?;
+    let y = read_fq(reader)?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::identity());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err("zkey contains a G1 point that is not on the curve".to_string());
+    }
+    Ok(point)
+}
+
+fn read_g2(reader: &mut BinReader) -> Result<G2Affine, String> {
+    let x = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    let y = Fq2::new(read_fq(reader)?, read_fq(reader)?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::identity());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("zkey contains an invalid G2 point".to_string());
+    }
+    Ok(point)
+}
+
+fn read_g1_vec(reader: &mut BinReader, len: usize) -> Result<Vec<G1Affine>, String> {
+    (0..len).map(|_| read_g1(reader)).collect()
+}
+
+fn read_g2_vec(reader: &mut BinReader, len: usize) -> Result<Vec<G2Affine>, String> {
+    (0..len).map(|_| read_g2(reader)).collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::circom::{parse_r1cs, tests::multiplier_r1cs, R1CS};
+    use crate::prover::{self, CircomReduction, R1CSCircuit};
+    use ark_groth16::Groth16;
+    use ark_snark::SNARK;
+    use rand::rngs::OsRng;
+
+    fn push_u32(out: &mut Vec<u8>, v: usize) {
+        out.extend_from_slice(&(v as u32).to_le_bytes());
+    }
+
+    fn push_bigint(out: &mut Vec<u8>, v: BigInt<4>) {
+        for limb in v.0 {
+            out.extend_from_slice(&limb.to_le_bytes());
+        }
+    }
+
+    fn push_g1(out: &mut Vec<u8>, p: &G1Affine) {
+        let (x, y) = if p.infinity { (Fq::zero(), Fq::zero()) } else { (p.x, p.y) };
+        push_bigint(out, x.0);
+        push_bigint(out, y.0);
+    }
+
+    fn push_g2(out: &mut Vec<u8>, p: &G2Affine) {
+        let (x, y) = if p.infinity { (Fq2::zero(), Fq2::zero()) } else { (p.x, p.y) };
+        for v in [x.c0, x.c1, y.c0, y.c1] {
+            push_bigint(out, v.0);
+        }
+    }
+
+    /// Encode a proving key and R1CS in the snarkjs zkey layout
+    pub(crate) fn encode_zkey(pk: &ProvingKey<Bn254>, r1cs: &R1CS) -> Vec<u8> {
+        let num_public = r1cs.num_instance() - 1;
+        let domain_size = pk.h_query.len();
+        // Coefficients carry an extra Montgomery factor: store v * R^2
+        let r_mont = Fr::from(BigUint::from(1u32) << 256);
+        let coeff = |v: Fr| (v * r_mont).0;
+
+        let mut header = Vec::new();
+        push_u32(&mut header, N8);
+        header.extend_from_slice(&BigUint::from(Fq::MODULUS).to_bytes_le());
+        push_u32(&mut header, N8);
+        header.extend_from_slice(&BigUint::from(Fr::MODULUS).to_bytes_le());
+        push_u32(&mut header, r1cs.num_wires);
+        push_u32(&mut header, num_public);
+        push_u32(&mut header, domain_size);
+        push_g1(&mut header, &pk.vk.alpha_g1);
+        push_g1(&mut header, &pk.beta_g1);
+        push_g2(&mut header, &pk.vk.beta_g2);
+        push_g2(&mut header, &pk.vk.gamma_g2);
+        push_g1(&mut header, &pk.delta_g1);
+        push_g2(&mut header, &pk.vk.delta_g2);
+
+        let mut entries = Vec::new();
+        for (i, (a, b, _)) in r1cs.constraints.iter().enumerate() {
+            entries.extend(a.iter().map(|(s, v)| (0, i, *s, *v)));
+            entries.extend(b.iter().map(|(s, v)| (1, i, *s, *v)));
+        }
+        for s in 0..=num_public {
+            entries.push((0, r1cs.constraints.len() + s, s, Fr::from(1u64)));
+        }
+        let mut coeffs = Vec::new();
+        push_u32(&mut coeffs, entries.len());
+        for (m, c, s, v) in entries {
+            push_u32(&mut coeffs, m);
+            push_u32(&mut coeffs, c);
+            push_u32(&mut coeffs, s);
+            push_bigint(&mut coeffs, coeff(v));
+        }
+
+        let g1s = |points: &[G1Affine]| {
+            let mut out = Vec::new();
+            points.iter().for_each(|p| push_g1(&mut out, p));
+            out
+        };
+        let mut b2 = Vec::new();
+        pk.b_g2_query.iter().for_each(|p| push_g2(&mut b2, p));
+
+        let sections: Vec<(u32, Vec<u8>)> = vec![
+            (ZKEY_SECTION_HEADER, ZKEY_PROTOCOL_GROTH16.to_le_bytes().to_vec()),
+            (ZKEY_SECTION_GROTH16_HEADER, header),
+            (ZKEY_SECTION_IC, g1s(&pk.vk.gamma_abc_g1)),
+            (ZKEY_SECTION_COEFFS, coeffs),
+            (ZKEY_SECTION_A, g1s(&pk.a_query)),
+            (ZKEY_SECTION_B1, g1s(&pk.b_g1_query)),
+            (ZKEY_SECTION_B2, b2),
+            (ZKEY_SECTION_C, g1s(&pk.l_query)),
+            (ZKEY_SECTION_H, g1s(&pk.h_query)),
+        ];
+        let mut out = b"zkey".to_vec();
+        push_u32(&mut out, 1);
+        push_u32(&mut out, sections.len());
+        for (ty, data) in sections {
+            out.extend_from_slice(&ty.to_le_bytes());
+            out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            out.extend_from_slice(&data);
+        }
+        out
+    }
+
+    /// Circom-reduction setup for the multiplier circuit, encoded as a zkey
+    pub(crate) fn multiplier_zkey() -> (Vec<u8>, VerifyingKey<Bn254>) {
+        let r1cs = parse_r1cs(&multiplier_r1cs()).unwrap();
+        let setup = R1CSCircuit { r1cs: &r1cs, witness: None };
+        let (pk, vk) =
+            Groth16::<Bn254, CircomReduction>::circuit_specific_setup(setup, &mut OsRng).unwrap();
+        (encode_zkey(&pk, &r1cs), vk)
+    }
+
+    #[test]
+    fn test_read_zkey_and_prove() {
+        let (zkey, vk) = multiplier_zkey();
+        assert!(is_zkey(&zkey));
+        let (pk, matrices) = read_zkey(&zkey).unwrap();
+        assert_eq!(pk.vk, vk);
+        assert_eq!(matrices.num_constraints, 1);
+        assert_eq!(matrices.num_instance_variables, 2);
+        assert_eq!(matrices.a[0], vec![(Fr::from(1u64), 2)]);
+
+        let witness: Vec<Fr> = [1u64, 35, 5, 7].iter().map(|v| Fr::from(*v)).collect();
+        let proof = prover::prove_with_matrices(&pk, &matrices, &witness).unwrap();
+        assert!(Groth16::<Bn254>::verify(&vk, &[Fr::from(35u64)], &proof).unwrap());
+    }
+
+    /// snarkjs stores points in Montgomery form (`2^256 mod q` is 1) and
+    /// coefficients times `R^2`, little-endian. These bytes are written out by
+    /// hand rather than with `encode_zkey`.
+    #[test]
+    fn test_snarkjs_encoding() {
+        let le = |hex: &str| {
+            let mut bytes = BigUint::parse_bytes(hex.as_bytes(), 16).unwrap().to_bytes_le();
+            bytes.resize(N8, 0);
+            bytes
+        };
+        // The G1 generator (1, 2)
+        let generator = [
+            le("0e0a77c19a07df2f666ea36f7879462c0a78eb28f5c70b3dd35d438dc58f0d9d"),
+            le("1c14ef83340fbe5eccdd46def0f28c5814f1d651eb8e167ba6ba871b8b1e1b3a"),
+        ]
+        .concat();
+        assert_eq!(
+            read_g1(&mut BinReader::new(&generator)).unwrap(),
+            G1Affine::new(Fq::from(1u64), Fq::from(2u64))
+        );
+
+        // The coefficients 1 and 7
+        let coefficients = [
+            le("0216d0b17f4e44a58c49833d53bb808553fe3ab1e35c59e31bb8e645ae216da7"),
+            le("0e9fb4da7b23e086d60296ad4a2083a54bf39add37867535c20e4be7c2e9ff91"),
+        ]
+        .concat();
+        let mut reader = BinReader::new(&coefficients);
+        assert_eq!(read_coefficient(&mut reader).unwrap(), Fr::from(1u64));
+        assert_eq!(read_coefficient(&mut reader).unwrap(), Fr::from(7u64));
+    }
+
+    #[test]
+    fn test_read_zkey_rejects_oversized_domain() {
+        let (mut zkey, _) = multiplier_zkey();
+        // magic, version, section count, protocol section, Groth16 section
+        // header, then n8q, q, n8r, r, num_vars and num_public
+        let offset = 12 + 16 + 12 + 4 + N8 + 4 + N8 + 8;
+        zkey[offset..offset + 4].copy_from_slice(&(1u32 << 31).to_le_bytes());
+        let err = read_zkey(&zkey).unwrap_err();
+        assert!(err.contains("domain size"), "{}", err);
+    }
+
+    #[test]
+    fn test_read_zkey_rejects_garbage() {
+        assert!(read_zkey(b"{\"protocol\": \"groth16\"}").is_err());
+        let (mut zkey, _) = multiplier_zkey();
+        zkey.truncate(zkey.len() - 10);
+        assert!(read_zkey(&zkey).is_err());
+    }
+}
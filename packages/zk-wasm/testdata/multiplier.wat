@@ -0,0 +1,84 @@
+;; Minimal stand-in for a circom 2.x witness generator of
+;;
+;;   template Multiplier() {
+;;       signal input a;
+;;       signal input b;
+;;       signal output c;
+;;       c <== a * b;
+;;   }
+;;
+;; Witness layout: [1, c, a, b]. Only the low 64 bits of `a * b` are computed,
+;; which is enough for small test values.
+(module
+  (import "runtime" "exceptionHandler" (func $exception (param i32)))
+  (memory (export "memory") 1)
+
+  ;; Shared RW memory lives at bytes [0, 32); witness slot i at 64 + 32 * i.
+  (func $slot (param $i i32) (result i32)
+    (i32.add (i32.const 64) (i32.mul (local.get $i) (i32.const 32))))
+
+  (func $copy (param $dst i32) (param $src i32)
+    (i64.store (local.get $dst) (i64.load (local.get $src)))
+    (i64.store offset=8 (local.get $dst) (i64.load offset=8 (local.get $src)))
+    (i64.store offset=16 (local.get $dst) (i64.load offset=16 (local.get $src)))
+    (i64.store offset=24 (local.get $dst) (i64.load offset=24 (local.get $src))))
+
+  ;; Map the low half of the FNV-1a name hash to a witness index.
+  (func $signal (param $lsb i32) (result i32)
+    (if (i32.eq (local.get $lsb) (i32.const -2046694260)) (then (return (i32.const 2))))
+    (if (i32.eq (local.get $lsb) (i32.const -2046692955)) (then (return (i32.const 3))))
+    (i32.const -1))
+
+  ;; Input values still to be set since the last `init`
+  (global $remaining (mut i32) (i32.const 0))
+
+  (func (export "getVersion") (result i32) (i32.const 2))
+  (func (export "getInputSize") (result i32) (i32.const 2))
+  (func (export "getRemainingInputsToBeSet") (result i32) (global.get $remaining))
+  (func (export "getFieldNumLen32") (result i32) (i32.const 8))
+  (func (export "getWitnessSize") (result i32) (i32.const 4))
+
+  (func (export "getRawPrime")
+    (i32.store (i32.const 0) (i32.const 4026531841))
+    (i32.store (i32.const 4) (i32.const 1138881939))
+    (i32.store (i32.const 8) (i32.const 2042196113))
+    (i32.store (i32.const 12) (i32.const 674490440))
+    (i32.store (i32.const 16) (i32.const 2172737629))
+    (i32.store (i32.const 20) (i32.const 3092268470))
+    (i32.store (i32.const 24) (i32.const 3778125865))
+    (i32.store (i32.const 28) (i32.const 811880050)))
+
+  (func (export "readSharedRWMemory") (param $i i32) (result i32)
+    (i32.load (i32.mul (local.get $i) (i32.const 4))))
+
+  (func (export "writeSharedRWMemory") (param $i i32) (param $v i32)
+    (i32.store (i32.mul (local.get $i) (i32.const 4)) (local.get $v)))
+
+  (func (export "init") (param $sanity i32)
+    (local $i i32)
+    (loop $zero
+      (i64.store (i32.add (i32.const 64) (local.get $i)) (i64.const 0))
+      (local.set $i (i32.add (local.get $i) (i32.const 8)))
+      (br_if $zero (i32.lt_u (local.get $i) (i32.const 128))))
+    (i64.store (call $slot (i32.const 0)) (i64.const 1))
+    (global.set $remaining (i32.const 2)))
+
+  (func (export "getInputSignalSize") (param $msb i32) (param $lsb i32) (result i32)
+    (if (result i32) (i32.ge_s (call $signal (local.get $lsb)) (i32.const 0))
+      (then (i32.const 1))
+      (else (i32.const -1))))
+
+  (func (export "setInputSignal") (param $msb i32) (param $lsb i32) (param $pos i32)
+    (local $idx i32)
+    (local.set $idx (call $signal (local.get $lsb)))
+    (if (i32.lt_s (local.get $idx) (i32.const 0))
+      (then (call $exception (i32.const 1)) (return)))
+    (call $copy (call $slot (local.get $idx)) (i32.const 0))
+    (global.set $remaining (i32.sub (global.get $remaining) (i32.const 1))))
+
+  (func (export "getWitness") (param $i i32)
+    (if (i32.eq (local.get $i) (i32.const 1))
+      (then
+        (i64.store (call $slot (i32.const 1))
+          (i64.mul (i64.load (call $slot (i32.const 2))) (i64.load (call $slot (i32.const 3)))))))
+    (call $copy (i32.const 0) (call $slot (local.get $i)))))